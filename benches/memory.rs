@@ -0,0 +1,81 @@
+//! Measure the memory used by the graphs considered while tallying
+//!
+//! Each graph is compared with the same edges stored as a `BTreeSet` of pairs, which is how they
+//! used to be stored.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    collections::BTreeSet,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use ranked_pairs::{Candidate, TabulatedData, TruncationMode};
+
+/// An allocator that keeps track of how much memory is in use
+struct CountingAlloc {
+    current: AtomicUsize,
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.current.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.current.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc {
+    current: AtomicUsize::new(0),
+};
+
+/// Get the memory still in use by the result of `f`, along with the result
+fn retained<T>(f: impl FnOnce() -> T) -> (usize, T) {
+    let start = ALLOC.current.load(Ordering::Relaxed);
+    let result = f();
+    (ALLOC.current.load(Ordering::Relaxed) - start, result)
+}
+
+/// Generate weighted ballots with `cycles` separate cycles of three candidates, which each have a
+/// different margin
+///
+/// Each cycle can be broken in three ways, so there are `3^cycles` possible final graphs.
+fn cyclic_ballots(cycles: Candidate) -> Vec<([Candidate; 2], u64)> {
+    (0..cycles)
+        .flat_map(|i| {
+            let weight = u64::from(i) + 1;
+            let (a, b, c) = (3 * i, 3 * i + 1, 3 * i + 2);
+            [([a, b], weight), ([b, c], weight), ([c, a], weight)]
+        })
+        .collect()
+}
+
+fn main() {
+    println!("candidates  graphs  bitset bytes  btree bytes");
+    for cycles in [2, 4, 6, 8] {
+        let candidates = 3 * cycles;
+        let data = TabulatedData::from_weighted_ballots_with(
+            cyclic_ballots(cycles),
+            candidates,
+            TruncationMode::Ignore,
+        )
+        .unwrap();
+
+        let (bitset, graphs) = retained(|| data.locked_graphs());
+        let (btree, _) = retained(|| {
+            graphs
+                .iter()
+                .map(|graph| graph.edges().collect::<BTreeSet<_>>())
+                .collect::<BTreeSet<_>>()
+        });
+
+        println!(
+            "{candidates:>10}  {:>6}  {bitset:>12}  {btree:>11}",
+            graphs.len()
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use ranked_pairs::{Candidate, TabulatedData};
+
+/// Generate pseudo-random ballots, each ranking a random number of candidates in a random order
+///
+/// The same seed always gives the same ballots.
+fn random_ballots(voters: usize, candidates: Candidate, seed: u64) -> Vec<Vec<Candidate>> {
+    // xorshift, which is plenty for generating test elections
+    let mut state = seed | 1;
+    let mut next = move |bound: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % bound as u64) as usize
+    };
+
+    (0..voters)
+        .map(|_| {
+            let mut ballot: Vec<Candidate> = (0..candidates).collect();
+            for i in (1..ballot.len()).rev() {
+                ballot.swap(i, next(i + 1));
+            }
+            ballot.truncate(1 + next(candidates as usize));
+            ballot
+        })
+        .collect()
+}
+
+/// Generate weighted ballots where `tied` of the pairwise wins have the same margin, and every
+/// other pairwise win has a different margin
+///
+/// Each group of equal-strength wins is locked in every possible order, so the time taken grows
+/// with the factorial of `tied`.
+fn tied_ballots(candidates: Candidate, tied: usize) -> Vec<(Vec<Candidate>, u64)> {
+    let pairs = (0..candidates).flat_map(|a| (a + 1..candidates).map(move |b| (a, b)));
+    pairs
+        .enumerate()
+        .flat_map(|(i, (a, b))| {
+            // the two ballots cancel out, except that `a` beats `b` on both
+            let others: Vec<Candidate> = (0..candidates).filter(|c| *c != a && *c != b).collect();
+            let first = [a, b].into_iter().chain(others.iter().copied()).collect();
+            let second = others.iter().rev().copied().chain([a, b]).collect();
+
+            let weight = 1 + i.saturating_sub(tied - 1) as u64;
+            [(first, weight), (second, weight)]
+        })
+        .collect()
+}
+
+/// Generate ballots made up of only a few distinct ballots, each repeated many times
+fn repeated_ballots(distinct: usize, copies: usize, candidates: Candidate) -> Vec<Vec<Candidate>> {
+    (0..distinct)
+        .flat_map(|i| {
+            // rotate the candidates a different amount for each distinct ballot
+            let ballot: Vec<Candidate> = (0..candidates)
+                .map(|c| (c + i as Candidate) % candidates)
+                .take(1 + i % candidates as usize)
+                .collect();
+            std::iter::repeat_n(ballot, copies)
+        })
+        .collect()
+}
+
+fn dedup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dedup");
+    for copies in [10, 100, 1000] {
+        let ballots = repeated_ballots(10, copies, 8);
+        group.bench_with_input(BenchmarkId::new("naive", copies), &ballots, |b, ballots| {
+            b.iter(|| TabulatedData::from_ballots(ballots, 8).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("dedup", copies), &ballots, |b, ballots| {
+            b.iter(|| TabulatedData::from_ballots_dedup(ballots, 8).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn tabulate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tabulate");
+    for voters in [100, 1000, 10000] {
+        let ballots = random_ballots(voters, 8, 1);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(voters),
+            &ballots,
+            |b, ballots| b.iter(|| TabulatedData::from_ballots(ballots, 8).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn tally(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tally");
+    for candidates in [4, 8, 16] {
+        let data =
+            TabulatedData::from_ballots(&random_ballots(10000, candidates, 2), candidates).unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(candidates), &data, |b, data| {
+            b.iter(|| data.tally())
+        });
+    }
+    group.finish();
+}
+
+fn tally_ties(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tally_ties");
+    for tied in [1, 2, 4, 6, 8] {
+        let ballots = tied_ballots(5, tied);
+        let data =
+            TabulatedData::from_weighted_ballots(ballots.iter().map(|(b, w)| (b, *w)), 5).unwrap();
+        assert_eq!(data.pairwise_results().last().unwrap().len(), tied);
+        group.bench_with_input(BenchmarkId::from_parameter(tied), &data, |b, data| {
+            b.iter(|| data.tally())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, tabulate, tally, tally_ties, dedup);
+criterion_main!(benches);
@@ -0,0 +1,30 @@
+//! Tabulate and tally arbitrary ballots, which should only ever give an error or a valid result
+//!
+//! Run with `cargo fuzz run tabulate`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ranked_pairs::{Candidate, TabulatedData};
+
+fuzz_target!(|data: &[u8]| {
+    let Some((candidates, ballots)) = data.split_first() else {
+        return;
+    };
+
+    // tallying takes time factorial in the number of tied pairs, so only use a few candidates,
+    // but allow ballots to have candidates that are not in the election
+    let candidates = Candidate::from(*candidates % 5);
+    let ballots: Vec<Vec<Candidate>> = ballots
+        .split(|byte| *byte == u8::MAX)
+        .map(|ballot| ballot.iter().map(|c| Candidate::from(*c % 8)).collect())
+        .collect();
+
+    let Ok(data) = TabulatedData::from_ballots(&ballots, candidates) else {
+        return;
+    };
+
+    let winners = data.tally();
+    assert_eq!(winners.is_empty(), candidates == 0);
+    assert!(winners.iter().all(|winner| *winner < candidates));
+});
@@ -0,0 +1,313 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    string::{String, ToString as _},
+    vec::Vec,
+};
+
+use crate::{Candidate, TabulatedData, TruncationMode, pairwise};
+
+/// An election read from a file in the BLT format used by OpenSTV
+///
+/// See [parse_blt].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BltElection {
+    /// The number of candidates
+    pub candidates: Candidate,
+    /// The number of seats to fill
+    pub seats: u32,
+    /// The candidates that withdrew, these are left off of every ballot
+    pub withdrawn: BTreeSet<Candidate>,
+    /// Each ballot, along with the number of voters who cast it
+    ///
+    /// A ballot is a list of ranks from first to last, where the candidates in each rank are
+    /// tied.
+    pub ballots: Vec<(Vec<Vec<Candidate>>, u64)>,
+    /// The name of each candidate
+    pub names: Vec<String>,
+    /// The title of the election, if one was given
+    pub title: Option<String>,
+}
+
+impl BltElection {
+    /// Tabulate the ballots
+    ///
+    /// Ballots without any tied ranks are counted as for [TabulatedData::from_weighted_ballots],
+    /// and candidates in the same rank are tied as for [TabulatedData::from_score_ballots].
+    pub fn tabulate(&self) -> TabulatedData {
+        let mut tabulator = pairwise::Tabulator::new(self.candidates, TruncationMode::default());
+        for (ranks, weight) in &self.ballots {
+            let counted = if ranks.iter().all(|rank| rank.len() == 1) {
+                let ballot: Vec<Candidate> = ranks.iter().flatten().copied().collect();
+                tabulator.add(&ballot, *weight)
+            } else {
+                // earlier ranks get higher scores
+                let scores: BTreeMap<Candidate, i32> = ranks
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(i, rank)| rank.iter().map(move |c| (*c, -(i as i32))))
+                    .collect();
+                tabulator.add_scores(&scores, *weight)
+            };
+            counted.expect("the ballots were checked while parsing");
+        }
+
+        TabulatedData::from_counts(tabulator.finish())
+    }
+}
+
+/// Read an election in the BLT format
+///
+/// The first line has the number of candidates and the number of seats, optionally followed by a
+/// line of withdrawn candidates as negative numbers. Each ballot is then a line with its weight
+/// followed by candidates from most to least preferred, ending with `0`. Candidates are numbered
+/// from one in the file (and from zero in the result), and candidates joined by `=` are tied. A
+/// line with just `0` ends the ballots, followed by the quoted name of every candidate and the
+/// quoted title of the election.
+///
+/// # Errors
+/// An error is returned for the first line that cannot be read, see [BltError]. Since tabulating
+/// takes memory for every pair of candidates, files with more than [MAX_INFERRED_CANDIDATES]
+/// candidates are rejected.
+///
+/// [MAX_INFERRED_CANDIDATES]: crate::MAX_INFERRED_CANDIDATES
+pub fn parse_blt(input: &str) -> Result<BltElection, BltError> {
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .peekable();
+
+    let (line, header) = lines.next().ok_or(BltError::UnexpectedEnd)?;
+    let mut header = header.split_whitespace();
+    let candidates = number(line, header.next())?;
+    let seats = number(line, header.next())?;
+    if header.next().is_some() {
+        return Err(BltError::InvalidNumber { line });
+    }
+    if candidates > crate::MAX_INFERRED_CANDIDATES {
+        return Err(BltError::TooManyCandidates);
+    }
+
+    let mut withdrawn = BTreeSet::new();
+    if let Some((line, list)) = lines.next_if(|(_, line)| line.starts_with('-')) {
+        for token in list.split_whitespace() {
+            let candidate: Candidate = number(line, token.strip_prefix('-'))?;
+            if candidate == 0 || candidate > candidates {
+                return Err(BltError::InvalidBallot { line });
+            }
+            withdrawn.insert(candidate - 1);
+        }
+    }
+
+    let mut ballots = Vec::new();
+    loop {
+        let (line, ballot) = lines.next().ok_or(BltError::UnexpectedEnd)?;
+        let mut tokens = ballot.split_whitespace();
+        let weight: u64 = number(line, tokens.next())?;
+        if weight == 0 {
+            // the end of the ballots
+            if tokens.next().is_some() {
+                return Err(BltError::InvalidBallot { line });
+            }
+            break;
+        }
+
+        let mut seen = BTreeSet::new();
+        let mut ranks = Vec::new();
+        let mut terminated = false;
+        for token in tokens {
+            if terminated {
+                return Err(BltError::InvalidBallot { line });
+            }
+            if token == "0" {
+                terminated = true;
+                continue;
+            }
+
+            let mut rank = Vec::new();
+            for candidate in token.split('=') {
+                let candidate: Candidate = number(line, Some(candidate))?;
+                if candidate == 0 || candidate > candidates || !seen.insert(candidate) {
+                    return Err(BltError::InvalidBallot { line });
+                }
+                if !withdrawn.contains(&(candidate - 1)) {
+                    rank.push(candidate - 1);
+                }
+            }
+            if !rank.is_empty() {
+                ranks.push(rank);
+            }
+        }
+        if !terminated {
+            return Err(BltError::InvalidBallot { line });
+        }
+
+        ballots.push((ranks, weight));
+    }
+
+    // the names may be spread across lines in any way
+    let mut strings = Vec::new();
+    let mut last = 0;
+    for (line, text) in lines {
+        let mut rest = text;
+        while !rest.is_empty() {
+            let (name, after) = rest
+                .strip_prefix('"')
+                .and_then(|quoted| quoted.split_once('"'))
+                .ok_or(BltError::InvalidName { line })?;
+            strings.push(name.to_string());
+            rest = after.trim_start();
+        }
+        last = line;
+    }
+
+    if strings.len() < candidates as usize {
+        return Err(BltError::UnexpectedEnd);
+    }
+    let title = strings.get(candidates as usize).cloned();
+    if strings.len() > candidates as usize + 1 {
+        return Err(BltError::InvalidName { line: last });
+    }
+    strings.truncate(candidates as usize);
+
+    Ok(BltElection {
+        candidates,
+        seats,
+        withdrawn,
+        ballots,
+        names: strings,
+        title,
+    })
+}
+
+fn number<T: core::str::FromStr>(line: usize, token: Option<&str>) -> Result<T, BltError> {
+    token
+        .and_then(|token| token.parse().ok())
+        .ok_or(BltError::InvalidNumber { line })
+}
+
+/// An error while reading a BLT file, see [parse_blt]
+///
+/// Lines are numbered from one.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BltError {
+    /// A number was missing or could not be read
+    #[error("invalid number on line {line}")]
+    InvalidNumber {
+        /// The line with the number
+        line: usize,
+    },
+    /// A ballot or withdrawn candidate was invalid
+    ///
+    /// This happens if a candidate is not in the election or is ranked more than once, or if a
+    /// ballot doesn't end with `0`.
+    #[error("invalid ballot on line {line}")]
+    InvalidBallot {
+        /// The line with the ballot
+        line: usize,
+    },
+    /// A name was not quoted, or there were too many names
+    #[error("invalid name on line {line}")]
+    InvalidName {
+        /// The line with the name
+        line: usize,
+    },
+    /// There were more than [MAX_INFERRED_CANDIDATES](crate::MAX_INFERRED_CANDIDATES) candidates
+    #[error("too many candidates")]
+    TooManyCandidates,
+    /// The file ended before every ballot and candidate name was read
+    #[error("unexpected end of file")]
+    UnexpectedEnd,
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use super::{BltError, parse_blt};
+    use crate::TabulatedData;
+
+    #[test]
+    fn parse() {
+        let election = parse_blt(
+            "4 1\n-4\n3 1 2 3 0\n2 2=3 1 0\n1 4 3 0\n0\n\"A\"\n\"B\"\n\"C c\" \"D\"\n\"Title\"\n",
+        )
+        .unwrap();
+        assert_eq!(election.candidates, 4);
+        assert_eq!(election.seats, 1);
+        assert_eq!(election.withdrawn, BTreeSet::from([3]));
+        assert_eq!(
+            election.ballots,
+            [
+                (vec![vec![0], vec![1], vec![2]], 3),
+                (vec![vec![1, 2], vec![0]], 2),
+                (vec![vec![2]], 1),
+            ]
+        );
+        assert_eq!(election.names, ["A", "B", "C c", "D"]);
+        assert_eq!(election.title.as_deref(), Some("Title"));
+    }
+
+    #[test]
+    fn tabulate() {
+        let election =
+            parse_blt("3 1\n3 1 2 3 0\n2 3 2 0\n1 2 0\n0\n\"A\"\n\"B\"\n\"C\"\n").unwrap();
+        assert_eq!(election.title, None);
+        assert_eq!(
+            election.tabulate(),
+            TabulatedData::from_weighted_ballots(
+                [
+                    ([0, 1, 2].as_slice(), 3),
+                    ([2, 1].as_slice(), 2),
+                    ([1].as_slice(), 1)
+                ],
+                3
+            )
+            .unwrap()
+        );
+
+        // tied ranks are the same as tied scores
+        let election = parse_blt("3 1\n2 1=2 3 0\n0\n\"A\"\n\"B\"\n\"C\"\n").unwrap();
+        let data = election.tabulate();
+        assert_eq!(data.total_ballots(), 2);
+        assert_eq!(data.tally(), BTreeSet::from([0, 1]));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse_blt(""), Err(BltError::UnexpectedEnd));
+        assert_eq!(parse_blt("x 1"), Err(BltError::InvalidNumber { line: 1 }));
+        assert_eq!(
+            parse_blt("4000000000 1\n0\n"),
+            Err(BltError::TooManyCandidates)
+        );
+        assert_eq!(
+            parse_blt("2 1\n\n1 1 3 0\n0\n"),
+            Err(BltError::InvalidBallot { line: 3 })
+        );
+        assert_eq!(
+            parse_blt("2 1\n1 1 1 0\n"),
+            Err(BltError::InvalidBallot { line: 2 })
+        );
+        assert_eq!(
+            parse_blt("2 1\n1 1 2\n"),
+            Err(BltError::InvalidBallot { line: 2 })
+        );
+        assert_eq!(parse_blt("2 1\n1 1 2 0\n"), Err(BltError::UnexpectedEnd));
+        assert_eq!(
+            parse_blt("2 1\n0\n\"A\"\n\"B\"\n"),
+            Ok(parse_blt("2 1\n0\n\"A\" \"B\"").unwrap())
+        );
+        assert_eq!(parse_blt("2 1\n0\n\"A\"\n"), Err(BltError::UnexpectedEnd));
+        assert_eq!(
+            parse_blt("2 1\n0\n\"A\"\nB\n"),
+            Err(BltError::InvalidName { line: 4 })
+        );
+        assert_eq!(
+            parse_blt("2 1\n0\n\"A\" \"B\" \"C\" \"D\""),
+            Err(BltError::InvalidName { line: 3 })
+        );
+    }
+}
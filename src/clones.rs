@@ -0,0 +1,165 @@
+use alloc::{collections::BTreeSet, vec, vec::Vec};
+
+use itertools::Itertools as _;
+
+use crate::{Candidate, Error};
+
+/// Find the maximal sets of clones in an election
+///
+/// A set of candidates are clones if no ballot ranks any other candidate between two of them.
+/// Unranked candidates are treated as tied below every ranked candidate, so a set that is only
+/// partly ranked on a ballot must also contain every other candidate left unranked on it. Each set
+/// returned has at least two candidates and is not every candidate, and is not contained in any
+/// other such set. The sets are given in sorted order.
+///
+/// # Errors
+/// See [TabulatedData::from_ballots](crate::TabulatedData::from_ballots).
+pub fn clone_sets<B: AsRef<[Candidate]>>(
+    ballots: &[B],
+    candidates: Candidate,
+) -> Result<Vec<BTreeSet<Candidate>>, Error> {
+    crate::validate_ballots(ballots, candidates)?;
+
+    // the position of each candidate on each ballot, with every unranked candidate at the end
+    let positions: Vec<Vec<usize>> = ballots
+        .iter()
+        .map(|ballot| {
+            let ballot = ballot.as_ref();
+            let mut position = vec![ballot.len(); candidates as usize];
+            for (i, c) in ballot.iter().enumerate() {
+                position[*c as usize] = i;
+            }
+            position
+        })
+        .collect();
+
+    // the smallest set of clones containing each pair
+    let closures: Vec<BTreeSet<Candidate>> = (0..candidates)
+        .tuple_combinations()
+        .map(|(a, b)| closure(&positions, candidates, BTreeSet::from([a, b])))
+        .filter(|set| set.len() < candidates as usize)
+        .collect();
+
+    // overlapping sets of clones can be merged, so for each candidate the sets of clones without
+    // them are the overlapping groups of the smallest sets without them; and every set of clones
+    // is missing some candidate
+    let mut sets = BTreeSet::new();
+    for missing in 0..candidates {
+        let mut groups: Vec<BTreeSet<Candidate>> = Vec::new();
+        for set in closures.iter().filter(|set| !set.contains(&missing)) {
+            let mut merged = set.clone();
+            groups.retain(|group| {
+                if group.is_disjoint(&merged) {
+                    true
+                } else {
+                    merged.extend(group);
+                    false
+                }
+            });
+            groups.push(merged);
+        }
+        sets.extend(groups);
+    }
+
+    Ok(sets
+        .iter()
+        .filter(|set| {
+            !sets
+                .iter()
+                .any(|other| set.is_subset(other) && set != &other)
+        })
+        .cloned()
+        .collect())
+}
+
+/// Grow `set` until no ballot ranks any other candidate between two of its members
+fn closure(
+    positions: &[Vec<usize>],
+    candidates: Candidate,
+    mut set: BTreeSet<Candidate>,
+) -> BTreeSet<Candidate> {
+    loop {
+        let size = set.len();
+        for position in positions {
+            let (low, high) = set
+                .iter()
+                .map(|c| position[*c as usize])
+                .minmax()
+                .into_option()
+                .expect("the set is never empty");
+            // unranked candidates are always last, and if every candidate is ranked this only
+            // matches the last one
+            let unranked = position.iter().copied().max().unwrap_or(0);
+
+            set.extend((0..candidates).filter(|c| {
+                let p = position[*c as usize];
+                // between two members, or unranked along with a member when another is ranked
+                (low < p && p < high) || (low < high && high == unranked && p == unranked)
+            }));
+        }
+
+        if set.len() == size {
+            return set;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use super::clone_sets;
+    use crate::Error;
+
+    #[test]
+    fn clone_sets_strict() {
+        let ballots = [[0, 1, 2, 3], [3, 1, 2, 0], [2, 1, 0, 3]];
+        assert_eq!(clone_sets(&ballots, 4), Ok(vec![BTreeSet::from([0, 1, 2])]));
+
+        // 0 and 1 are clones, and so are 2 and 3
+        let ballots = [[0, 1, 2, 3], [3, 2, 1, 0], [2, 3, 0, 1]];
+        assert_eq!(
+            clone_sets(&ballots, 4),
+            Ok(vec![BTreeSet::from([0, 1]), BTreeSet::from([2, 3])])
+        );
+
+        // the same ranking from everyone makes every run of candidates clones
+        let ballots = [[0, 1, 2]; 3];
+        assert_eq!(
+            clone_sets(&ballots, 3),
+            Ok(vec![BTreeSet::from([0, 1]), BTreeSet::from([1, 2])])
+        );
+    }
+
+    #[test]
+    fn clone_sets_unranked() {
+        // with no rankings every set is clones
+        assert_eq!(
+            clone_sets(&[[0; 0]; 0], 3),
+            Ok(vec![
+                BTreeSet::from([0, 1]),
+                BTreeSet::from([0, 2]),
+                BTreeSet::from([1, 2])
+            ])
+        );
+
+        // 1 and 2 are clones since 2 is unranked right after 1, but 0 and 2 are split by 1
+        assert_eq!(
+            clone_sets(&[[0, 1]], 3),
+            Ok(vec![BTreeSet::from([0, 1]), BTreeSet::from([1, 2])])
+        );
+
+        // 1, 2 and 3 are not clones since 0 is between them on the second ballot
+        assert_eq!(
+            clone_sets(&[[0, 1], [1, 0]], 4),
+            Ok(vec![BTreeSet::from([0, 1]), BTreeSet::from([2, 3])])
+        );
+
+        assert_eq!(clone_sets(&[[0; 0]; 0], 2), Ok(vec![]));
+        assert_eq!(clone_sets(&[[0; 0]; 0], 0), Ok(vec![]));
+        assert_eq!(
+            clone_sets(&[[0, 0]], 2),
+            Err(Error::InvalidBallot { ballot: 0 })
+        );
+    }
+}
@@ -0,0 +1,400 @@
+use alloc::{collections::BTreeSet, vec::Vec};
+
+use itertools::Itertools as _;
+
+use crate::{Candidate, Error, TabulatedData};
+
+/// The most candidates that [TabulatedData::kemeny_ranking] will consider
+pub const MAX_KEMENY_CANDIDATES: Candidate = 8;
+
+impl TabulatedData {
+    /// Get the Condorcet winner, if there is one
+    ///
+    /// The Condorcet winner is the candidate who beats every other candidate in their pairwise
+    /// election. When there is a Condorcet winner, they are the only winner of
+    /// [TabulatedData::tally].
+    pub fn condorcet_winner(&self) -> Option<Candidate> {
+        (0..self.candidates)
+            .find(|c| (0..self.candidates).all(|other| other == *c || self.beats(*c, other)))
+    }
+
+    /// Get the Condorcet loser, if there is one
+    ///
+    /// The Condorcet loser is the candidate who loses to every other candidate in their pairwise
+    /// election. There is never a Condorcet loser with fewer than two candidates, and the
+    /// Condorcet loser is never a winner of [TabulatedData::tally].
+    pub fn condorcet_loser(&self) -> Option<Candidate> {
+        if self.candidates < 2 {
+            // a single candidate would be both the Condorcet winner and loser
+            return None;
+        }
+
+        (0..self.candidates)
+            .find(|c| (0..self.candidates).all(|other| other == *c || self.beats(other, *c)))
+    }
+
+    /// Get the Smith set
+    ///
+    /// The Smith set is the smallest non-empty set of candidates such that every candidate in the
+    /// set beats every candidate outside of it. The winners of [TabulatedData::tally] are always
+    /// in the Smith set.
+    pub fn smith_set(&self) -> BTreeSet<Candidate> {
+        // the candidates in the top strongly connected component of the beats-or-ties graph can
+        // reach every other candidate
+        let reachable = self.reachability(|a, b| !self.beats(b, a));
+        (0..self.candidates)
+            .filter(|a| reachable[*a as usize].iter().all(|r| *r))
+            .collect()
+    }
+
+    /// Get the Schwartz set
+    ///
+    /// The Schwartz set is the union of every minimal set of candidates that are unbeaten by any
+    /// candidate outside of the set. This is always a subset of the [Smith
+    /// set](TabulatedData::smith_set), and they only differ when there are pairwise ties.
+    pub fn schwartz_set(&self) -> BTreeSet<Candidate> {
+        // the candidates in any unbeaten strongly connected component of the beats graph can reach
+        // every candidate that can reach them
+        let reachable = self.reachability(|a, b| self.beats(a, b));
+        (0..self.candidates)
+            .filter(|a| {
+                (0..self.candidates).all(|b| {
+                    !reachable[b as usize][*a as usize] || reachable[*a as usize][b as usize]
+                })
+            })
+            .collect()
+    }
+
+    /// Get the Copeland score of every candidate
+    ///
+    /// Each candidate's score is the number of pairwise elections they win, minus the number they
+    /// lose. Ties count for neither. The scores are indexed by candidate number.
+    pub fn copeland_scores(&self) -> Vec<i32> {
+        (0..self.candidates)
+            .map(|a| {
+                (0..self.candidates)
+                    .map(|b| self.counts.margin(a, b).signum() as i32)
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Get the minimax (Simpson-Kramer) winners
+    ///
+    /// Each candidate's worst pairwise defeat is the largest margin they lose by, or zero if they
+    /// are never beaten. The winners are the candidates whose worst defeat is the smallest. Like
+    /// ranked pairs this always elects the Condorcet winner, but otherwise they can disagree.
+    pub fn minimax_winner(&self) -> BTreeSet<Candidate> {
+        let worst_defeat = |a: Candidate| {
+            (0..self.candidates)
+                .map(|b| self.counts.margin(b, a).max(0))
+                .max()
+                .unwrap_or(0)
+        };
+
+        let Some(best) = (0..self.candidates).map(worst_defeat).min() else {
+            return BTreeSet::new();
+        };
+        (0..self.candidates)
+            .filter(|a| worst_defeat(*a) == best)
+            .collect()
+    }
+
+    /// Get the Kemeny-Young ranking, from most to least preferred
+    ///
+    /// This is the ordering of the candidates that disagrees with the fewest pairwise preferences
+    /// on the ballots. If several orderings are equally good, the lexicographically smallest is
+    /// given. Every ordering is checked, so this takes `O(n! * n^2)` time for `n` candidates.
+    ///
+    /// # Errors
+    /// [Error::TooManyCandidates] is returned if there are more than [MAX_KEMENY_CANDIDATES]
+    /// candidates.
+    pub fn kemeny_ranking(&self) -> Result<Vec<Candidate>, Error> {
+        if self.candidates > MAX_KEMENY_CANDIDATES {
+            return Err(Error::TooManyCandidates);
+        }
+
+        // the ballots agreeing minus the ballots disagreeing, which is best when the disagreement
+        // is smallest
+        let score = |order: &[Candidate]| -> i64 {
+            order
+                .iter()
+                .tuple_combinations()
+                .map(|(a, b)| self.counts.margin(*a, *b))
+                .sum()
+        };
+
+        let mut best: Option<(i64, Vec<Candidate>)> = None;
+        for order in (0..self.candidates).permutations(self.candidates as usize) {
+            let score = score(&order);
+            if best.as_ref().is_none_or(|(best, _)| score > *best) {
+                best = Some((score, order));
+            }
+        }
+
+        Ok(best.map(|(_, order)| order).unwrap_or_default())
+    }
+
+    /// Find which candidates can reach which others, following the given edges
+    ///
+    /// Every candidate can reach itself.
+    fn reachability(&self, edge: impl Fn(Candidate, Candidate) -> bool) -> Vec<Vec<bool>> {
+        let size = self.candidates as usize;
+        let mut reachable: Vec<Vec<bool>> = (0..self.candidates)
+            .map(|a| (0..self.candidates).map(|b| a == b || edge(a, b)).collect())
+            .collect();
+
+        // Floyd-Warshall, for the transitive closure
+        for k in 0..size {
+            let through_k = reachable[k].clone();
+            for row in &mut reachable {
+                if row[k] {
+                    for (r, t) in row.iter_mut().zip(&through_k) {
+                        *r |= *t;
+                    }
+                }
+            }
+        }
+
+        reachable
+    }
+
+    /// Check if `winner` strictly beats `loser` in their pairwise election
+    pub(crate) fn beats(&self, winner: Candidate, loser: Candidate) -> bool {
+        self.counts.wins(winner, loser) > self.counts.wins(loser, winner)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use itertools::Itertools as _;
+
+    use super::MAX_KEMENY_CANDIDATES;
+    use crate::{Error, TabulatedData};
+
+    #[test]
+    fn condorcet_winner() {
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+            if let Some(winner) = data.condorcet_winner() {
+                assert_eq!(data.tally(), BTreeSet::from([winner]));
+            }
+        }
+
+        let data = TabulatedData::from_ballots(&crate::test::tideman_example_2_ballots(), 5);
+        assert_eq!(data.unwrap().condorcet_winner(), None);
+
+        let data = TabulatedData::from_ballots(&crate::test::tideman_example_3_ballots(), 3);
+        assert_eq!(data.unwrap().condorcet_winner(), Some(2));
+
+        assert_eq!(
+            TabulatedData::from_ballots([[0; 0]; 0], 0)
+                .unwrap()
+                .condorcet_winner(),
+            None
+        );
+        assert_eq!(
+            TabulatedData::from_ballots([[0; 0]; 0], 1)
+                .unwrap()
+                .condorcet_winner(),
+            Some(0)
+        );
+
+        // a pairwise tie means there is no Condorcet winner
+        assert_eq!(
+            TabulatedData::from_ballots([[0, 1, 2], [1, 0, 2]], 3)
+                .unwrap()
+                .condorcet_winner(),
+            None
+        );
+    }
+
+    #[test]
+    fn condorcet_loser() {
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+            if let Some(loser) = data.condorcet_loser() {
+                assert!(!data.tally().contains(&loser));
+            }
+        }
+
+        let data = TabulatedData::from_ballots(&crate::test::tideman_example_4_ballots(), 4);
+        assert_eq!(data.unwrap().condorcet_loser(), Some(3));
+
+        let data = TabulatedData::from_ballots(&crate::test::tideman_example_6_ballots(), 4);
+        assert_eq!(data.unwrap().condorcet_loser(), None);
+
+        for candidates in 0..2 {
+            assert_eq!(
+                TabulatedData::from_ballots([[0; 0]; 0], candidates)
+                    .unwrap()
+                    .condorcet_loser(),
+                None
+            );
+        }
+
+        assert_eq!(
+            TabulatedData::from_ballots([[0, 1]], 2)
+                .unwrap()
+                .condorcet_loser(),
+            Some(1)
+        );
+
+        // a pairwise tie means there is no Condorcet loser
+        assert_eq!(
+            TabulatedData::from_ballots([[2, 0, 1], [2, 1, 0]], 3)
+                .unwrap()
+                .condorcet_loser(),
+            None
+        );
+    }
+
+    #[test]
+    fn smith_set() {
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+            assert!(data.tally().is_subset(&data.smith_set()));
+        }
+
+        let data = TabulatedData::from_ballots(&crate::test::tideman_example_4_ballots(), 4);
+        // 0 beats 1 beats 2 beats 0, and all of them beat 3
+        assert_eq!(data.unwrap().smith_set(), BTreeSet::from([0, 1, 2]));
+
+        let data = TabulatedData::from_ballots(&crate::test::tideman_example_6_ballots(), 4);
+        assert_eq!(data.unwrap().smith_set(), BTreeSet::from([0, 1, 2, 3]));
+
+        let data = TabulatedData::from_ballots(&crate::test::tideman_example_3_ballots(), 3);
+        assert_eq!(data.unwrap().smith_set(), BTreeSet::from([2]));
+
+        // tied candidates are both in the Smith set
+        assert_eq!(
+            TabulatedData::from_ballots([[0, 1, 2], [1, 0, 2]], 3)
+                .unwrap()
+                .smith_set(),
+            BTreeSet::from([0, 1])
+        );
+
+        assert_eq!(
+            TabulatedData::from_ballots([[0; 0]; 0], 0)
+                .unwrap()
+                .smith_set(),
+            BTreeSet::new()
+        );
+    }
+
+    #[test]
+    fn copeland_scores() {
+        assert_eq!(
+            TabulatedData::from_ballots(&crate::test::tideman_example_4_ballots(), 4)
+                .unwrap()
+                .copeland_scores(),
+            [1, 1, 1, -3]
+        );
+
+        // 0 ties 1, 1 beats 2, 2 beats 0
+        let data =
+            TabulatedData::from_ballots([[0, 1, 2], [1, 2, 0], [1, 2, 0], [2, 0, 1]], 3).unwrap();
+        assert_eq!(data.copeland_scores(), [-1, 1, 0]);
+
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+            assert_eq!(data.copeland_scores().iter().sum::<i32>(), 0);
+        }
+    }
+
+    #[test]
+    fn minimax_winner() {
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+            if let Some(winner) = data.condorcet_winner() {
+                assert_eq!(data.minimax_winner(), BTreeSet::from([winner]));
+            }
+        }
+
+        // 0 only loses to 1, by 2, so it wins minimax; but ranked pairs overrules 2 over 1 (by 4)
+        // with 1 over 3 over 2 (by 6 each), and then locks in 1 over 0
+        let data = TabulatedData::from_weighted_ballots(
+            [
+                ([1, 0, 2, 3].as_slice(), 4),
+                ([3, 1, 0, 2].as_slice(), 4),
+                ([0, 1, 3, 2].as_slice(), 6),
+                ([2, 1, 3, 0].as_slice(), 9),
+                ([0, 3, 2, 1].as_slice(), 9),
+            ],
+            4,
+        )
+        .unwrap();
+        assert_eq!(data.minimax_winner(), BTreeSet::from([0]));
+        assert_eq!(data.tally(), BTreeSet::from([1]));
+
+        // with no defeats at all, everyone ties
+        let data = TabulatedData::from_ballots([[0; 0]; 0], 3).unwrap();
+        assert_eq!(data.minimax_winner(), BTreeSet::from([0, 1, 2]));
+        let data = TabulatedData::from_ballots([[0; 0]; 0], 0).unwrap();
+        assert_eq!(data.minimax_winner(), BTreeSet::new());
+    }
+
+    #[test]
+    fn kemeny_ranking() {
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+            let ranking = data.kemeny_ranking().unwrap();
+            assert_eq!(
+                ranking.iter().sorted().copied().collect::<Vec<_>>(),
+                (0..candidates).collect::<Vec<_>>()
+            );
+            if let Some(winner) = data.condorcet_winner() {
+                assert_eq!(ranking[0], winner);
+            }
+        }
+
+        // 0 beats 1 by 3, 1 beats 2 by 5, and 2 beats 0 by 1
+        let data = TabulatedData::from_weighted_ballots(
+            [([0, 1, 2], 4), ([1, 2, 0], 3), ([2, 0, 1], 2)],
+            3,
+        )
+        .unwrap();
+        assert_eq!(data.kemeny_ranking(), Ok(vec![0, 1, 2]));
+
+        // every ordering is as good, so the smallest is chosen
+        let data = TabulatedData::from_ballots([[0; 0]; 0], 3).unwrap();
+        assert_eq!(data.kemeny_ranking(), Ok(vec![0, 1, 2]));
+        let data = TabulatedData::from_ballots([[0; 0]; 0], 0).unwrap();
+        assert_eq!(data.kemeny_ranking(), Ok(vec![]));
+
+        let data = TabulatedData::from_ballots([[0; 0]; 0], MAX_KEMENY_CANDIDATES + 1).unwrap();
+        assert_eq!(data.kemeny_ranking(), Err(Error::TooManyCandidates));
+    }
+
+    #[test]
+    fn schwartz_set() {
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+            // there are no pairwise ties in these examples
+            assert_eq!(data.schwartz_set(), data.smith_set());
+        }
+
+        // 0 ties 1, 1 beats 2, 2 beats 0
+        let data =
+            TabulatedData::from_ballots([[0, 1, 2], [1, 2, 0], [1, 2, 0], [2, 0, 1]], 3).unwrap();
+        assert_eq!(data.smith_set(), BTreeSet::from([0, 1, 2]));
+        assert_eq!(data.schwartz_set(), BTreeSet::from([1]));
+        assert_eq!(data.tally(), BTreeSet::from([1]));
+
+        // candidates tied with everyone are unbeaten
+        assert_eq!(
+            TabulatedData::from_ballots([[0, 1, 2], [1, 0, 2]], 3)
+                .unwrap()
+                .schwartz_set(),
+            BTreeSet::from([0, 1])
+        );
+        assert_eq!(
+            TabulatedData::from_ballots([[0; 0]; 0], 2)
+                .unwrap()
+                .schwartz_set(),
+            BTreeSet::from([0, 1])
+        );
+    }
+}
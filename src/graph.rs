@@ -1,110 +1,915 @@
-use std::collections::BTreeSet;
+use alloc::{
+    collections::BTreeSet,
+    string::{String, ToString as _},
+    vec,
+    vec::Vec,
+};
 
-#[derive(Clone, PartialEq, Eq, Hash)]
+use itertools::Itertools as _;
+
+use crate::Candidate;
+
+/// A directed acyclic graph of candidates
+///
+/// Each node is a candidate number, and an edge from `a` to `b` means that `a` is ranked above `b`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct AcyclicGraph {
-    nodes: u16,
-    edges: BTreeSet<(u16, u16)>,
+    nodes: Candidate,
+    // a bitset of the edges from each node, so every graph is a single allocation of
+    // `nodes^2 / 64` words however many edges it has
+    edges: Vec<u64>,
 }
 
 impl AcyclicGraph {
-    pub fn new(nodes: u16) -> Self {
+    pub(crate) fn new(nodes: Candidate) -> Self {
         Self {
             nodes,
-            edges: BTreeSet::new(),
+            edges: vec![0; nodes as usize * row_len(nodes)],
         }
     }
 
+    /// Create a graph from a list of edges, as `(src, dst)`
+    ///
+    /// Repeated edges are only added once.
+    ///
+    /// # Errors
+    /// An error is returned for the first edge that would create a cycle, or that has a node that
+    /// is not in the graph (`>= nodes`).
+    pub fn from_edges(
+        nodes: Candidate,
+        edges: impl IntoIterator<Item = (Candidate, Candidate)>,
+    ) -> Result<Self, CycleError> {
+        let mut graph = Self::new(nodes);
+        for (src, dst) in edges {
+            if src >= nodes || dst >= nodes {
+                return Err(CycleError::InvalidNode { src, dst });
+            }
+
+            if graph.reaches(dst, src) {
+                return Err(CycleError::Cycle { src, dst });
+            }
+
+            graph.insert(src, dst);
+        }
+
+        Ok(graph)
+    }
+
+    /// Try to add an edge, fails if it would cause a cycle
+    ///
+    /// Returns true if adding the edge succeeds and it is new, so it is false if either node is
+    /// not in the graph.
+    pub(crate) fn try_add_edge(&mut self, src: Candidate, dst: Candidate) -> bool {
+        src < self.nodes && dst < self.nodes && !self.reaches(dst, src) && self.insert(src, dst)
+    }
+
     /// Try to add an edge, fails if it would cause a cycle
     ///
-    /// Returns true if adding the edge succeeds and it is new.
-    pub fn try_add_edge(&mut self, src: u16, dst: u16) -> bool {
-        assert!(src < self.nodes);
-        assert!(dst < self.nodes);
+    /// This is the same as [AcyclicGraph::try_add_edge], but checks for a cycle using `closure`,
+    /// which must be the transitive closure of this graph, and keeps it up to date.
+    pub(crate) fn try_add_edge_with(
+        &mut self,
+        closure: &mut Closure,
+        src: Candidate,
+        dst: Candidate,
+    ) -> bool {
+        if src >= self.nodes || dst >= self.nodes {
+            return false;
+        }
 
-        if src != dst && self.dfs(dst).all(|e| e != src) {
-            self.edges.insert((src, dst))
+        if !closure.is_reachable(dst, src) && self.insert(src, dst) {
+            closure.add_edge(src, dst);
+            true
         } else {
             false
         }
     }
 
-    pub fn roots(&self) -> impl Iterator<Item = u16> {
-        #[allow(clippy::single_range_in_vec_init)]
-        let mut elements = rangemap::RangeSet::new();
-        if self.nodes > 0 {
-            elements.insert(0..self.nodes);
-            for e in &self.edges {
-                elements.remove(e.1..e.1 + 1);
-            }
+    /// Check whether there is a path from `src` to `dst`
+    ///
+    /// Every node can reach itself.
+    ///
+    /// # Errors
+    /// [CycleError::InvalidNode] is returned if either node is not in the graph.
+    pub fn is_reachable(&self, src: Candidate, dst: Candidate) -> Result<bool, CycleError> {
+        if src >= self.nodes || dst >= self.nodes {
+            return Err(CycleError::InvalidNode { src, dst });
         }
-        elements.into_iter().flatten()
+
+        Ok(self.reaches(src, dst))
     }
 
-    fn dfs(&self, start: u16) -> impl Iterator<Item = u16> {
-        debug_assert!(start < self.nodes);
+    /// Check whether there is a path from `src` to `dst`, both of which must be in the graph
+    fn reaches(&self, src: Candidate, dst: Candidate) -> bool {
+        debug_assert!(src < self.nodes);
+        debug_assert!(dst < self.nodes);
 
-        Dfs {
-            graph: self,
-            visited: Vec::new(),
-            node: start,
+        if src == dst {
+            return true;
         }
+
+        // depth-first search, visiting each node at most once
+        let mut visited = vec![false; self.nodes as usize];
+        let mut stack = vec![src];
+        while let Some(node) = stack.pop() {
+            for next in self.outgoing(node) {
+                if next == dst {
+                    return true;
+                }
+                if !visited[next as usize] {
+                    visited[next as usize] = true;
+                    stack.push(next);
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Check whether adding an edge from `src` to `dst` would create a cycle
+    ///
+    /// This is true for an edge from a node to itself. The graph is not changed.
+    ///
+    /// # Errors
+    /// [CycleError::InvalidNode] is returned if either node is not in the graph.
+    pub fn would_cycle(&self, src: Candidate, dst: Candidate) -> Result<bool, CycleError> {
+        if src >= self.nodes || dst >= self.nodes {
+            return Err(CycleError::InvalidNode { src, dst });
+        }
+
+        Ok(self.reaches(dst, src))
     }
 
-    fn outgoing(&self, src: u16) -> impl Iterator<Item = u16> {
+    /// Find a path from `src` to `dst`, as the nodes visited including both ends
+    pub(crate) fn path(&self, src: Candidate, dst: Candidate) -> Option<Vec<Candidate>> {
         debug_assert!(src < self.nodes);
+        debug_assert!(dst < self.nodes);
 
+        // depth-first search, remembering where each node was first reached from
+        let mut parent = vec![None; self.nodes as usize];
+        let mut stack = vec![src];
+        while let Some(node) = stack.pop() {
+            if node == dst {
+                let mut path = vec![dst];
+                while let Some(prev) = parent[*path.last().unwrap() as usize] {
+                    path.push(prev);
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            for next in self.outgoing(node) {
+                if next != src && parent[next as usize].is_none() {
+                    parent[next as usize] = Some(node);
+                    stack.push(next);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Get the number of nodes in the graph
+    ///
+    /// The nodes are numbered from zero, so every node is less than this.
+    pub fn nodes(&self) -> Candidate {
+        self.nodes
+    }
+
+    /// Get every edge in the graph, as `(src, dst)`
+    ///
+    /// The edges are given in sorted order.
+    pub fn edges(&self) -> impl Iterator<Item = (Candidate, Candidate)> {
+        (0..self.nodes).flat_map(move |src| self.outgoing(src).map(move |dst| (src, dst)))
+    }
+
+    /// Get the number of edges in the graph
+    pub fn edge_count(&self) -> usize {
         self.edges
             .iter()
-            .copied()
-            .filter_map(move |(s, d)| (s == src).then_some(d))
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Check whether two graphs have the same edges, whatever the number of nodes in each
+    ///
+    /// Unlike `==`, this treats graphs that only differ in their isolated nodes as equal.
+    pub fn eq_relation(&self, other: &Self) -> bool {
+        self.edges().eq(other.edges())
+    }
+
+    /// Get the graph with the fewest edges that has the same reachability as this one
+    ///
+    /// Each edge that is implied by a longer path is removed, which leaves a tidier graph for
+    /// [AcyclicGraph::to_dot].
+    pub fn transitive_reduction(&self) -> AcyclicGraph {
+        let closure = Closure::new(self);
+        let len = row_len(self.nodes);
+        let mut edges = self.edges.clone();
+        for src in 0..self.nodes {
+            let row = &mut edges[src as usize * len..][..len];
+            for next in self.outgoing(src) {
+                // anything reachable from another destination doesn't need its own edge
+                for (word, implied) in row.iter_mut().zip(closure.row(next)) {
+                    *word &= !implied;
+                }
+            }
+        }
+
+        AcyclicGraph {
+            nodes: self.nodes,
+            edges,
+        }
+    }
+
+    /// Get the graph with an edge from each node to every node it can reach
+    ///
+    /// This has the same reachability as this graph, so it is also acyclic, but
+    /// [AcyclicGraph::is_reachable] only needs to check for a single edge.
+    pub fn transitive_closure(&self) -> AcyclicGraph {
+        // the closure is stored in the same layout as the edges
+        AcyclicGraph {
+            nodes: self.nodes,
+            edges: Closure::new(self).reachable,
+        }
+    }
+
+    /// Get the roots of the graph (nodes with no incoming edges)
+    ///
+    /// The roots are given in ascending order.
+    pub fn roots(&self) -> impl Iterator<Item = Candidate> {
+        // every node with an edge to it from any node
+        let mut beaten = vec![0; row_len(self.nodes)];
+        for src in 0..self.nodes {
+            for (word, row) in beaten.iter_mut().zip(self.row(src)) {
+                *word |= row;
+            }
+        }
+
+        (0..self.nodes).filter(move |node| {
+            let (word, mask) = bit(*node);
+            beaten[word] & mask == 0
+        })
+    }
+
+    /// Get the roots of the graph, in ascending order
+    ///
+    /// This is the same as collecting [AcyclicGraph::roots].
+    pub fn roots_vec(&self) -> Vec<Candidate> {
+        self.roots().collect()
+    }
+
+    /// Get the sinks of the graph (nodes with no outgoing edges)
+    ///
+    /// The sinks are given in ascending order.
+    pub fn sinks(&self) -> impl Iterator<Item = Candidate> {
+        (0..self.nodes).filter(|node| self.row(*node).iter().all(|word| *word == 0))
+    }
+
+    /// Get the roots of the graph, where nodes joined by ties are grouped together
+    ///
+    /// Each tie `(a, b)` is a weak, undirected edge, and the ties are considered in ascending
+    /// order. A tie joins the groups of `a` and `b` only if no node in one group can reach a node
+    /// in the other, so nodes are only grouped when they are connected by nothing but ties. Every
+    /// node in a group that has no incoming edges from outside of the group is a root. With no
+    /// ties, this is the same as [AcyclicGraph::roots].
+    ///
+    /// The roots are given in ascending order.
+    ///
+    /// # Panics
+    /// Panics if any tie has a node that is not in the graph.
+    pub fn roots_with_ties(&self, ties: &BTreeSet<(Candidate, Candidate)>) -> Vec<Candidate> {
+        // the group of each node, named by one of the nodes in it
+        let mut group: Vec<Candidate> = (0..self.nodes).collect();
+        for (a, b) in ties.iter().copied() {
+            let (from, to) = (group[b as usize], group[a as usize]);
+            let group_ref = &group;
+            let members =
+                |g: Candidate| (0..self.nodes).filter(move |n| group_ref[*n as usize] == g);
+            let ordered = from == to
+                || members(from)
+                    .cartesian_product(members(to).collect::<Vec<_>>())
+                    .any(|(x, y)| self.reaches(x, y) || self.reaches(y, x));
+            if !ordered {
+                for g in &mut group {
+                    if *g == from {
+                        *g = to;
+                    }
+                }
+            }
+        }
+
+        let beaten: BTreeSet<Candidate> = self
+            .edges()
+            .filter(|(src, dst)| group[*src as usize] != group[*dst as usize])
+            .map(|(_, dst)| group[dst as usize])
+            .collect();
+        (0..self.nodes)
+            .filter(|node| !beaten.contains(&group[*node as usize]))
+            .collect()
+    }
+
+    /// Get the roots of the subgraph made up of only the given nodes
+    pub fn subgraph_roots(&self, nodes: &BTreeSet<Candidate>) -> impl Iterator<Item = Candidate> {
+        nodes.iter().copied().filter(|node| {
+            // nodes that are not in the graph have no edges at all
+            *node >= self.nodes
+                || !nodes
+                    .range(..self.nodes)
+                    .any(|src| self.contains(*src, *node))
+        })
+    }
+
+    /// Get every topological ordering of the graph
+    ///
+    /// Each ordering contains every node, and every edge goes from a node earlier in the ordering
+    /// to one later in it. The orderings are generated lazily, in lexicographic order.
+    pub fn all_topological_orders(&self) -> impl Iterator<Item = Vec<Candidate>> {
+        let outgoing: Vec<Vec<Candidate>> = (0..self.nodes)
+            .map(|node| self.outgoing(node).collect())
+            .collect();
+        let mut incoming = vec![0; self.nodes as usize];
+        for (_, dst) in self.edges() {
+            incoming[dst as usize] += 1;
+        }
+
+        let mut orders = TopologicalOrders {
+            outgoing,
+            incoming,
+            placed: vec![false; self.nodes as usize],
+            order: Vec::new(),
+            stack: Vec::new(),
+            empty: self.nodes == 0,
+        };
+        let choices = orders.choices();
+        orders.stack.push((choices, 0));
+        orders
+    }
+
+    /// Get the length of the longest path to each node from any root
+    ///
+    /// Roots have a length of zero, and every other node is one more than the longest length of
+    /// any node with an edge to it. The lengths are indexed by node.
+    pub fn longest_path_lengths(&self) -> Vec<usize> {
+        let mut incoming = vec![0; self.nodes as usize];
+        for (_, dst) in self.edges() {
+            incoming[dst as usize] += 1;
+        }
+
+        // visit each node after every node with an edge to it
+        let mut lengths = vec![0; self.nodes as usize];
+        let mut ready: Vec<Candidate> = self.roots().collect();
+        while let Some(src) = ready.pop() {
+            for dst in self.outgoing(src) {
+                lengths[dst as usize] = lengths[dst as usize].max(lengths[src as usize] + 1);
+                incoming[dst as usize] -= 1;
+                if incoming[dst as usize] == 0 {
+                    ready.push(dst);
+                }
+            }
+        }
+
+        lengths
+    }
+
+    /// Export the graph in the Graphviz DOT format
+    ///
+    /// Each node is labeled with its number. See [AcyclicGraph::to_dot_with_labels].
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with_labels(|node| node.to_string())
+    }
+
+    /// Export the graph in the Graphviz DOT format, using the given node labels
+    ///
+    /// The output is a `digraph`, with an edge from each winner to each loser. Roots of the graph
+    /// are drawn filled.
+    pub fn to_dot_with_labels(&self, label: impl Fn(Candidate) -> String) -> String {
+        use core::fmt::Write as _;
+
+        let roots: BTreeSet<Candidate> = self.roots().collect();
+
+        let mut dot = String::from("digraph {\n");
+        for node in 0..self.nodes {
+            // escape the label so that it is always a valid quoted string
+            let label = label(node).replace('\\', "\\\\").replace('"', "\\\"");
+            let style = if roots.contains(&node) {
+                ", style=filled"
+            } else {
+                ""
+            };
+            writeln!(dot, "    {node} [label=\"{label}\"{style}];").unwrap();
+        }
+        for (src, dst) in self.edges() {
+            writeln!(dot, "    {src} -> {dst};").unwrap();
+        }
+        dot.push('}');
+        dot.push('\n');
+
+        dot
+    }
+
+    fn outgoing(&self, src: Candidate) -> impl Iterator<Item = Candidate> {
+        ones(self.row(src))
+    }
+
+    fn row(&self, src: Candidate) -> &[u64] {
+        debug_assert!(src < self.nodes);
+
+        let len = row_len(self.nodes);
+        &self.edges[src as usize * len..][..len]
+    }
+
+    fn contains(&self, src: Candidate, dst: Candidate) -> bool {
+        let (word, mask) = bit(dst);
+        self.row(src)[word] & mask != 0
+    }
+
+    /// Add an edge, returns true if it is new
+    fn insert(&mut self, src: Candidate, dst: Candidate) -> bool {
+        let (word, mask) = bit(dst);
+        let word = &mut self.edges[src as usize * row_len(self.nodes) + word];
+        let new = *word & mask == 0;
+        *word |= mask;
+        new
     }
 }
 
-struct Dfs<'g> {
-    graph: &'g AcyclicGraph,
-    visited: Vec<(u16, u16)>,
-    node: u16,
+/// Get the number of words in a bitset of `nodes`
+fn row_len(nodes: Candidate) -> usize {
+    (nodes as usize).div_ceil(64)
 }
 
-impl Iterator for Dfs<'_> {
-    type Item = u16;
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.graph.outgoing(self.node).next() {
-            Some(dst) => {
-                self.visited.push((self.node, dst));
-                self.node = dst;
-                Some(dst)
+/// Get the word and mask of a node in a bitset
+fn bit(node: Candidate) -> (usize, u64) {
+    (node as usize / 64, 1 << (node % 64))
+}
+
+/// Get every node in a bitset, in ascending order
+fn ones(row: &[u64]) -> impl Iterator<Item = Candidate> {
+    row.iter().enumerate().flat_map(|(i, word)| {
+        let mut word = *word;
+        core::iter::from_fn(move || {
+            (word != 0).then(|| {
+                let node = i as Candidate * 64 + word.trailing_zeros();
+                // clear the lowest bit
+                word &= word - 1;
+                node
+            })
+        })
+    })
+}
+
+struct TopologicalOrders {
+    outgoing: Vec<Vec<Candidate>>,
+    // the number of incoming edges from nodes that have not been placed yet
+    incoming: Vec<usize>,
+    placed: Vec<bool>,
+    order: Vec<Candidate>,
+    // for each position in the order, each node that can go there and the index of the next one
+    stack: Vec<(Vec<Candidate>, usize)>,
+    // the empty graph has exactly one (empty) ordering, which has not been given yet
+    empty: bool,
+}
+
+impl TopologicalOrders {
+    /// Get every node that can be placed next
+    fn choices(&self) -> Vec<Candidate> {
+        (0..self.placed.len())
+            .filter(|node| !self.placed[*node] && self.incoming[*node] == 0)
+            .map(|node| node as Candidate)
+            .collect()
+    }
+
+    fn place(&mut self, node: Candidate) {
+        self.placed[node as usize] = true;
+        self.order.push(node);
+        for dst in &self.outgoing[node as usize] {
+            self.incoming[*dst as usize] -= 1;
+        }
+    }
+
+    fn unplace(&mut self) {
+        if let Some(node) = self.order.pop() {
+            self.placed[node as usize] = false;
+            for dst in &self.outgoing[node as usize] {
+                self.incoming[*dst as usize] += 1;
             }
-            None => loop {
-                match self.visited.pop() {
-                    Some(last) => {
-                        let mut cursor = self
-                            .graph
-                            .edges
-                            .upper_bound(std::ops::Bound::Included(&last));
-                        while let Some((src, dst)) = cursor.next().copied() {
-                            if src == last.0 {
-                                self.visited.push((src, dst));
-                                self.node = dst;
-                                return Some(dst);
-                            }
-                        }
+        }
+    }
+}
+
+impl Iterator for TopologicalOrders {
+    type Item = Vec<Candidate>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.empty {
+            self.empty = false;
+            return Some(Vec::new());
+        }
+
+        loop {
+            let (choices, next) = self.stack.last_mut()?;
+            match choices.get(*next).copied() {
+                Some(node) => {
+                    *next += 1;
+                    self.place(node);
+                    if self.order.len() == self.placed.len() {
+                        // every node has been placed, so this is a complete ordering
+                        let order = self.order.clone();
+                        self.unplace();
+                        return Some(order);
                     }
-                    None => return None,
+                    let choices = self.choices();
+                    self.stack.push((choices, 0));
+                }
+                None => {
+                    // every choice at this position has been tried, so go back one
+                    self.stack.pop();
+                    self.unplace();
+                }
+            }
+        }
+    }
+}
+
+impl core::iter::FusedIterator for TopologicalOrders {}
+
+/// The largest graph that a [Closure] is kept for while locking in pairs
+///
+/// A closure takes `nodes^2 / 64` words, so this limits each one to 2 MiB.
+pub(crate) const MAX_CLOSURE_NODES: Candidate = 4096;
+
+/// The transitive closure of a graph, so that reachability can be checked in constant time
+#[derive(Clone)]
+pub(crate) struct Closure {
+    nodes: Candidate,
+    // a bitset of the nodes reachable from each node, not including itself
+    reachable: Vec<u64>,
+}
+
+impl Closure {
+    pub(crate) fn new(graph: &AcyclicGraph) -> Self {
+        let mut closure = Self {
+            nodes: graph.nodes,
+            reachable: vec![0; graph.nodes as usize * row_len(graph.nodes)],
+        };
+        for (src, dst) in graph.edges() {
+            closure.add_edge(src, dst);
+        }
+        closure
+    }
+
+    fn row(&self, src: Candidate) -> &[u64] {
+        let len = row_len(self.nodes);
+        &self.reachable[src as usize * len..][..len]
+    }
+
+    fn is_reachable(&self, src: Candidate, dst: Candidate) -> bool {
+        let (word, mask) = bit(dst);
+        src == dst || self.row(src)[word] & mask != 0
+    }
+
+    /// Update the closure for a new edge
+    fn add_edge(&mut self, src: Candidate, dst: Candidate) {
+        // everything that reaches `src` can now reach `dst` and everything it reaches
+        let mut added = self.row(dst).to_vec();
+        let (word, mask) = bit(dst);
+        added[word] |= mask;
+
+        let len = added.len();
+        for node in 0..self.nodes {
+            if self.is_reachable(node, src) {
+                let row = &mut self.reachable[node as usize * len..][..len];
+                for (word, added) in row.iter_mut().zip(&added) {
+                    *word |= added;
                 }
-            },
+            }
         }
     }
 }
 
-impl std::iter::FusedIterator for Dfs<'_> {}
+/// An error while creating a graph, see [AcyclicGraph::from_edges]
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CycleError {
+    /// An edge would have created a cycle
+    #[error("edge from {src} to {dst} would create a cycle")]
+    Cycle {
+        /// The source of the edge
+        src: Candidate,
+        /// The destination of the edge
+        dst: Candidate,
+    },
+    /// An edge, or a pair of nodes to check, had a node that is not in the graph
+    #[error("edge from {src} to {dst} has a node that is not in the graph")]
+    InvalidNode {
+        /// The source of the edge
+        src: Candidate,
+        /// The destination of the edge
+        dst: Candidate,
+    },
+}
 
 #[cfg(test)]
 mod test {
-    use crate::graph::AcyclicGraph;
+    use std::collections::BTreeSet;
+
+    use itertools::Itertools as _;
+
+    use crate::{
+        Candidate,
+        graph::{AcyclicGraph, Closure, CycleError},
+    };
+
+    #[test]
+    fn subgraph_roots() {
+        let mut graph = AcyclicGraph::new(4);
+        assert!(graph.try_add_edge(0, 1));
+        assert!(graph.try_add_edge(0, 2));
+        assert!(graph.try_add_edge(1, 3));
+
+        assert_eq!(graph.roots().collect::<Vec<_>>(), &[0]);
+        assert_eq!(graph.sinks().collect::<Vec<_>>(), &[2, 3]);
+        assert_eq!(
+            graph
+                .subgraph_roots(&BTreeSet::from([1, 2, 3]))
+                .collect::<Vec<_>>(),
+            &[1, 2]
+        );
+        assert_eq!(
+            graph
+                .subgraph_roots(&BTreeSet::from([0, 3]))
+                .collect::<Vec<_>>(),
+            &[0, 3]
+        );
+    }
+
+    #[test]
+    fn from_edges() {
+        let graph = AcyclicGraph::from_edges(4, [(0, 1), (1, 2), (0, 1), (0, 2)]).unwrap();
+        assert_eq!(graph.edges().collect::<Vec<_>>(), [(0, 1), (0, 2), (1, 2)]);
+        assert_eq!(graph.nodes(), 4);
+
+        let mut expected = AcyclicGraph::new(4);
+        assert!(expected.try_add_edge(1, 2));
+        assert!(expected.try_add_edge(0, 1));
+        assert!(expected.try_add_edge(0, 2));
+        assert_eq!(graph, expected);
+
+        assert_eq!(
+            AcyclicGraph::from_edges(3, [(0, 1), (1, 2), (2, 0)]),
+            Err(CycleError::Cycle { src: 2, dst: 0 })
+        );
+        assert_eq!(
+            AcyclicGraph::from_edges(3, [(1, 1)]),
+            Err(CycleError::Cycle { src: 1, dst: 1 })
+        );
+        assert_eq!(
+            AcyclicGraph::from_edges(3, [(0, 1), (1, 3)]),
+            Err(CycleError::InvalidNode { src: 1, dst: 3 })
+        );
+        assert_eq!(AcyclicGraph::from_edges(0, []), Ok(AcyclicGraph::new(0)));
+    }
+
+    #[test]
+    fn eq_relation() {
+        let graph = AcyclicGraph::from_edges(3, [(0, 1), (1, 2)]).unwrap();
+        let larger = AcyclicGraph::from_edges(5, [(1, 2), (0, 1)]).unwrap();
+        assert_ne!(graph, larger);
+        assert!(graph.eq_relation(&larger));
+        assert!(larger.eq_relation(&graph));
+
+        let other = AcyclicGraph::from_edges(3, [(0, 1), (0, 2)]).unwrap();
+        assert!(!graph.eq_relation(&other));
+        assert!(AcyclicGraph::new(0).eq_relation(&AcyclicGraph::new(2)));
+    }
+
+    #[test]
+    fn transitive_reduction() {
+        // a chain with shortcuts, and a separate edge
+        let graph =
+            AcyclicGraph::from_edges(6, [(0, 1), (1, 2), (2, 3), (0, 2), (0, 3), (1, 3), (4, 5)])
+                .unwrap();
+        let reduced = graph.transitive_reduction();
+        assert_eq!(
+            reduced,
+            AcyclicGraph::from_edges(6, [(0, 1), (1, 2), (2, 3), (4, 5)]).unwrap()
+        );
+        for (src, dst) in (0..6).cartesian_product(0..6) {
+            assert_eq!(reduced.is_reachable(src, dst), graph.is_reachable(src, dst));
+        }
+        assert_eq!(reduced.transitive_reduction(), reduced);
+
+        // a diamond has no redundant edges
+        let diamond = AcyclicGraph::from_edges(4, [(0, 1), (0, 2), (1, 3), (2, 3)]).unwrap();
+        assert_eq!(diamond.transitive_reduction(), diamond);
+        assert_eq!(
+            AcyclicGraph::new(0).transitive_reduction(),
+            AcyclicGraph::new(0)
+        );
+    }
+
+    #[test]
+    fn transitive_closure() {
+        let graph = AcyclicGraph::from_edges(70, [(0, 1), (1, 2), (2, 69), (4, 5)]).unwrap();
+        let closure = graph.transitive_closure();
+        assert_eq!(
+            closure.edges().collect::<Vec<_>>(),
+            [(0, 1), (0, 2), (0, 69), (1, 2), (1, 69), (2, 69), (4, 5)]
+        );
+        assert_eq!(
+            AcyclicGraph::from_edges(70, closure.edges()),
+            Ok(closure.clone())
+        );
+        assert_eq!(closure.transitive_closure(), closure);
+        assert_eq!(closure.transitive_reduction(), graph);
+        assert_eq!(
+            AcyclicGraph::new(0).transitive_closure(),
+            AcyclicGraph::new(0)
+        );
+    }
+
+    #[test]
+    fn roots_with_ties() {
+        let mut graph = AcyclicGraph::new(5);
+        assert!(graph.try_add_edge(0, 2));
+        assert!(graph.try_add_edge(2, 3));
+        assert!(graph.try_add_edge(4, 3));
+
+        assert_eq!(graph.roots_with_ties(&BTreeSet::new()), [0, 1, 4]);
+
+        // 1 is grouped with 2, which is beaten by 0
+        assert_eq!(graph.roots_with_ties(&BTreeSet::from([(1, 2)])), [0, 4]);
+
+        // 0 and 1 are grouped, but 2 is not joined to them since it is beaten by 0, and 0 and 3,
+        // and 3 and 4, are already ordered
+        assert_eq!(
+            graph.roots_with_ties(&BTreeSet::from([(0, 1), (1, 2), (0, 3), (3, 4)])),
+            [0, 1, 4]
+        );
+    }
+
+    #[test]
+    fn is_reachable() {
+        let mut graph = AcyclicGraph::new(5);
+        assert!(graph.try_add_edge(0, 1));
+        assert!(graph.try_add_edge(1, 2));
+        assert!(graph.try_add_edge(3, 2));
+        assert_eq!(graph.nodes(), 5);
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(graph.edges().collect::<Vec<_>>(), [(0, 1), (1, 2), (3, 2)]);
+
+        assert_eq!(graph.roots_vec(), [0, 3, 4]);
+        assert_eq!(graph.roots_vec(), graph.roots().collect::<Vec<_>>());
+
+        assert_eq!(graph.is_reachable(0, 2), Ok(true));
+        assert_eq!(graph.is_reachable(3, 2), Ok(true));
+        assert_eq!(graph.is_reachable(4, 4), Ok(true));
+        assert_eq!(graph.is_reachable(2, 0), Ok(false));
+        assert_eq!(graph.is_reachable(0, 3), Ok(false));
+        assert_eq!(graph.is_reachable(0, 4), Ok(false));
+        assert_eq!(
+            graph.is_reachable(0, 5),
+            Err(CycleError::InvalidNode { src: 0, dst: 5 })
+        );
+
+        assert_eq!(graph.would_cycle(2, 0), Ok(true));
+        assert_eq!(graph.would_cycle(4, 4), Ok(true));
+        assert_eq!(graph.would_cycle(0, 1), Ok(false));
+        assert_eq!(graph.would_cycle(2, 4), Ok(false));
+        assert_eq!(
+            graph.would_cycle(5, 0),
+            Err(CycleError::InvalidNode { src: 5, dst: 0 })
+        );
+        assert_eq!(graph.edge_count(), 3);
+
+        // edges that would cause a cycle are rejected
+        assert!(!graph.try_add_edge(2, 0));
+        assert!(!graph.try_add_edge(4, 4));
+        assert!(!graph.try_add_edge(0, 1));
+        assert!(!graph.try_add_edge(0, 5));
+        assert!(graph.try_add_edge(2, 4));
+    }
 
     #[test]
-    fn dfs() {
+    fn closure() {
+        // more than one word per row
+        let nodes = 70;
+
+        let mut graph = AcyclicGraph::new(nodes);
+        let mut with_closure = AcyclicGraph::new(nodes);
+        let mut closure = Closure::new(&with_closure);
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..300 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let src = (state % 70) as Candidate;
+            let dst = ((state >> 32) % 70) as Candidate;
+            assert_eq!(
+                with_closure.try_add_edge_with(&mut closure, src, dst),
+                graph.try_add_edge(src, dst)
+            );
+        }
+        assert_eq!(with_closure, graph);
+
+        // compare against Floyd-Warshall
+        let size = nodes as usize;
+        let mut reachable = vec![vec![false; size]; size];
+        for (src, dst) in graph.edges() {
+            reachable[src as usize][dst as usize] = true;
+        }
+        for k in 0..size {
+            for i in 0..size {
+                for j in 0..size {
+                    reachable[i][j] |= reachable[i][k] && reachable[k][j];
+                }
+            }
+        }
+
+        let rebuilt = Closure::new(&graph);
+        for src in 0..nodes {
+            for dst in 0..nodes {
+                let expected = src == dst || reachable[src as usize][dst as usize];
+                assert_eq!(graph.is_reachable(src, dst), Ok(expected));
+                assert_eq!(closure.is_reachable(src, dst), expected);
+                assert_eq!(rebuilt.is_reachable(src, dst), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn longest_path_lengths() {
+        let graph = AcyclicGraph::from_edges(6, [(0, 1), (1, 2), (0, 2), (3, 2), (2, 4)]).unwrap();
+        assert_eq!(graph.longest_path_lengths(), [0, 1, 2, 0, 3, 0]);
+
+        assert_eq!(AcyclicGraph::new(3).longest_path_lengths(), [0, 0, 0]);
+        assert!(AcyclicGraph::new(0).longest_path_lengths().is_empty());
+    }
+
+    #[test]
+    fn topological_orders() {
+        let mut graph = AcyclicGraph::new(4);
+        assert!(graph.try_add_edge(0, 1));
+        assert!(graph.try_add_edge(0, 2));
+        assert!(graph.try_add_edge(1, 3));
+
+        assert_eq!(
+            graph.all_topological_orders().collect::<Vec<_>>(),
+            [[0, 1, 2, 3], [0, 1, 3, 2], [0, 2, 1, 3],]
+        );
+
+        // with no edges, every permutation is an ordering
+        assert_eq!(AcyclicGraph::new(4).all_topological_orders().count(), 24);
+        assert_eq!(
+            AcyclicGraph::new(0)
+                .all_topological_orders()
+                .collect::<Vec<_>>(),
+            [Vec::<Candidate>::new()]
+        );
+
+        // the orderings are generated lazily, so this doesn't take 20! steps
+        assert_eq!(
+            AcyclicGraph::new(20).all_topological_orders().nth(1),
+            Some(vec![
+                0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 19, 18
+            ])
+        );
+    }
+
+    #[test]
+    fn dot() {
+        let mut graph = AcyclicGraph::new(3);
+        assert!(graph.try_add_edge(0, 1));
+        assert!(graph.try_add_edge(2, 1));
+
+        assert_eq!(
+            graph.to_dot(),
+            "digraph {
+    0 [label=\"0\", style=filled];
+    1 [label=\"1\"];
+    2 [label=\"2\", style=filled];
+    0 -> 1;
+    2 -> 1;
+}
+"
+        );
+
+        let names = ["Alice", "Bob", "\"Eve\""];
+        assert_eq!(
+            graph.to_dot_with_labels(|node| names[node as usize].to_string()),
+            "digraph {
+    0 [label=\"Alice\", style=filled];
+    1 [label=\"Bob\"];
+    2 [label=\"\\\"Eve\\\"\", style=filled];
+    0 -> 1;
+    2 -> 1;
+}
+"
+        );
+    }
+
+    #[test]
+    fn reachable_tree() {
         let mut graph = AcyclicGraph::new(12);
 
         // root to each child
@@ -125,8 +930,22 @@ mod test {
         assert!(graph.try_add_edge(3, 7));
 
         assert_eq!(
-            graph.dfs(8).collect::<Vec<_>>(),
-            &[2, 3, 4, 5, 6, 7, 9, 1, 10, 0, 11]
+            (0..12)
+                .filter(|node| graph.is_reachable(8, *node) == Ok(true))
+                .collect::<Vec<_>>(),
+            &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]
+        );
+        assert_eq!(
+            (0..12)
+                .filter(|node| graph.is_reachable(9, *node) == Ok(true))
+                .collect::<Vec<_>>(),
+            &[0, 1, 9, 10, 11]
+        );
+        assert_eq!(
+            (0..12)
+                .filter(|node| graph.is_reachable(*node, 6) == Ok(true))
+                .collect::<Vec<_>>(),
+            &[3, 4, 6, 8]
         );
     }
 }
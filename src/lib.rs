@@ -1,103 +1,1337 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![warn(missing_docs)]
-#![feature(btree_cursors)]
 
 //! Ranked pairs (Tideman method) election method, with handling of ties.
+//!
+//! The `std` feature is enabled by default. Without it the crate only needs `alloc`, and the
+//! graphs considered while tallying are kept in a [BTreeSet] instead of a `HashSet`. The public
+//! API is the same either way.
 
+extern crate alloc;
+
+#[cfg(feature = "blt")]
+mod blt;
+mod clones;
+mod condorcet;
 mod graph;
 mod pairwise;
+mod report;
+mod tiebreak;
 
 #[cfg(test)]
 mod test;
 
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    string::{String, ToString as _},
+    vec::Vec,
+};
 
 use itertools::Itertools as _;
 
+#[cfg(feature = "blt")]
+pub use blt::{BltElection, BltError, parse_blt};
+pub use clones::clone_sets;
+pub use condorcet::MAX_KEMENY_CANDIDATES;
+pub use graph::{AcyclicGraph, CycleError};
+pub use report::{BallotStats, CriteriaReport, LockStep, TallyOutcome, TallyReport, TallySummary};
+
+/// A candidate number
+///
+/// Candidates are numbered from zero, so with `n` candidates every candidate number is less than
+/// `n`.
+pub type Candidate = u32;
+
 /// Tabulated data for an election
 ///
 /// This contains the minimal data needed from the ballots to be able to compute the winners,
 /// without storing all of the ballots.
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub struct TabulatedData {
-    table: BTreeMap<usize, BTreeSet<(u16, u16)>>,
-    candidates: u16,
+    table: BTreeMap<u64, BTreeSet<(Candidate, Candidate)>>,
+    counts: pairwise::PairwiseCounts,
+    candidates: Candidate,
 }
 
 impl TabulatedData {
     /// Create the data from a set of ballots
     ///
-    /// Each ballot consists of a list of choices in order, candidate numbers are zero-based.
+    /// Each ballot consists of a list of choices in order, candidate numbers are zero-based. Every
+    /// ranked candidate beats every unranked candidate, see [TruncationMode].
     ///
     /// # Errors
     /// An error will be returned if any ballot contains an invalid candidate number (`>= candidates`)
     /// or contains the same candidate more than once.
-    pub fn from_ballots<B: AsRef<[u16]>>(
+    pub fn from_ballots<B: AsRef<[Candidate]>>(
+        ballots: impl IntoIterator<Item = B> + Copy,
+        candidates: Candidate,
+    ) -> Result<Self, Error> {
+        Self::from_weighted_ballots(pairwise::Unweighted(ballots), candidates)
+    }
+
+    /// Create the data from a set of ballots, taking the number of candidates from the ballots
+    ///
+    /// This is the same as [TabulatedData::from_ballots], except that the number of candidates is
+    /// one more than the largest candidate number on any ballot (or zero if there are no choices
+    /// on any ballot).
+    ///
+    /// # Errors
+    /// An error will be returned for the first ballot that contains the same candidate more than
+    /// once, or a candidate of at least [MAX_INFERRED_CANDIDATES] (since tabulating takes memory
+    /// for every pair of candidates).
+    pub fn from_ballots_infer<B: AsRef<[Candidate]>>(
+        ballots: impl IntoIterator<Item = B> + Copy,
+    ) -> Result<Self, Error> {
+        let max = ballots
+            .into_iter()
+            .flat_map(|ballot| ballot.as_ref().iter().copied().max())
+            .max();
+
+        // any candidate that is too large is then reported as invalid, in ballot order
+        let candidates = max.map_or(0, |c| c.saturating_add(1).min(MAX_INFERRED_CANDIDATES));
+        Self::from_ballots(ballots, candidates)
+    }
+
+    /// Create the data from a set of ballots, adding any write-in candidates
+    ///
+    /// This is the same as [TabulatedData::from_ballots], except that instead of rejecting
+    /// candidate numbers that are too large, the number of candidates grows to include them, up
+    /// to `limit`. The pairwise results take `candidates^2` words, so the limit stops a single
+    /// mistyped write-in from taking all of the memory; [MAX_INFERRED_CANDIDATES] is a reasonable
+    /// default.
+    ///
+    /// # Errors
+    /// An error will be returned for the first ballot that contains the same candidate more than
+    /// once, or a candidate that is at least both `candidates` and `limit`.
+    pub fn from_ballots_growing<B: AsRef<[Candidate]>>(
+        ballots: impl IntoIterator<Item = B> + Copy,
+        candidates: Candidate,
+        limit: Candidate,
+    ) -> Result<Self, Error> {
+        let seen = ballots
+            .into_iter()
+            .flat_map(|ballot| ballot.as_ref().iter().copied().max())
+            .max();
+
+        // any candidate that is too large is then reported as invalid, in ballot order
+        let grown = seen.map_or(0, |c| c.saturating_add(1).min(limit));
+        Self::from_ballots(ballots, candidates.max(grown))
+    }
+
+    /// Create the data from a set of weighted ballots
+    ///
+    /// Each item is a ballot, as in [TabulatedData::from_ballots], along with the number of voters
+    /// who cast that ballot. This gives the same result as repeating each ballot, without needing
+    /// to store every copy.
+    ///
+    /// # Errors
+    /// An error will be returned if any ballot is invalid, including ballots with a weight of zero.
+    /// See [TabulatedData::from_ballots].
+    pub fn from_weighted_ballots<B: AsRef<[Candidate]>>(
+        ballots: impl IntoIterator<Item = (B, u64)> + Clone,
+        candidates: Candidate,
+    ) -> Result<Self, Error> {
+        Self::from_weighted_ballots_with(ballots, candidates, TruncationMode::default())
+    }
+
+    /// Create the data from a set of ballots with fractional weights
+    ///
+    /// This is the same as [TabulatedData::from_weighted_ballots], but each weight is counted in
+    /// fixed point, as the nearest multiple of `1 / WEIGHT_SCALE`. The margins and vote counts are
+    /// then in those units, so a margin of [WEIGHT_SCALE] is a margin of one ballot, and the
+    /// results are compared as if the weights were exact up to that precision.
+    ///
+    /// # Errors
+    /// [Error::InvalidWeight] is returned if a weight is negative or not finite, or is too large
+    /// to be scaled. Otherwise, see [TabulatedData::from_weighted_ballots].
+    pub fn from_weighted_ballots_f64<B: AsRef<[Candidate]>>(
+        ballots: impl IntoIterator<Item = (B, f64)>,
+        candidates: Candidate,
+    ) -> Result<Self, Error> {
+        let max_weight = (u64::MAX / WEIGHT_SCALE) as f64;
+        let ballots = ballots
+            .into_iter()
+            .enumerate()
+            .map(|(index, (ballot, weight))| {
+                pairwise::check_ballot(index, ballot.as_ref(), candidates)?;
+                if !(0.0..=max_weight).contains(&weight) {
+                    return Err(Error::InvalidWeight { ballot: index });
+                }
+
+                // round to the nearest multiple, this doesn't need `f64::round` from `std`
+                Ok((ballot, (weight * WEIGHT_SCALE as f64 + 0.5) as u64))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::from_weighted_ballots(
+            ballots
+                .iter()
+                .map(|(ballot, weight)| (ballot.as_ref(), *weight)),
+            candidates,
+        )
+    }
+
+    /// Create the data from a set of ballots, using the given treatment of unranked candidates
+    ///
+    /// This is the same as [TabulatedData::from_ballots], which uses
+    /// [TruncationMode::RankedBeatsUnranked].
+    ///
+    /// # Errors
+    /// See [TabulatedData::from_ballots].
+    pub fn from_ballots_with<B: AsRef<[Candidate]>>(
         ballots: impl IntoIterator<Item = B> + Copy,
-        candidates: u16,
+        candidates: Candidate,
+        truncation: TruncationMode,
     ) -> Result<Self, Error> {
+        Self::from_weighted_ballots_with(pairwise::Unweighted(ballots), candidates, truncation)
+    }
+
+    /// Create the data from a set of weighted ballots, using the given treatment of unranked
+    /// candidates
+    ///
+    /// This is the same as [TabulatedData::from_weighted_ballots], which uses
+    /// [TruncationMode::RankedBeatsUnranked].
+    ///
+    /// # Errors
+    /// See [TabulatedData::from_weighted_ballots].
+    pub fn from_weighted_ballots_with<B: AsRef<[Candidate]>>(
+        ballots: impl IntoIterator<Item = (B, u64)> + Clone,
+        candidates: Candidate,
+        truncation: TruncationMode,
+    ) -> Result<Self, Error> {
+        Ok(Self::from_counts(pairwise::count_pairwise_results(
+            ballots, candidates, truncation,
+        )?))
+    }
+
+    fn from_counts(counts: pairwise::PairwiseCounts) -> Self {
+        Self {
+            table: counts.tabulate_by(|winner_votes, loser_votes| winner_votes - loser_votes),
+            candidates: counts.candidates(),
+            counts,
+        }
+    }
+
+    /// Create the data from a stream of ballots
+    ///
+    /// This gives the same result as [TabulatedData::from_ballots], but the ballots are only
+    /// iterated over once, and each one is dropped after it has been counted. This allows
+    /// tabulating ballots as they are read, without collecting all of them.
+    ///
+    /// # Errors
+    /// See [TabulatedData::from_ballots].
+    pub fn from_ballots_iter<B: AsRef<[Candidate]>>(
+        ballots: impl IntoIterator<Item = B>,
+        candidates: Candidate,
+    ) -> Result<Self, Error> {
+        let mut tabulator = pairwise::Tabulator::new(candidates, TruncationMode::default());
+        for ballot in ballots {
+            tabulator.add(ballot.as_ref(), 1)?;
+        }
+
+        Ok(Self::from_counts(tabulator.finish()))
+    }
+
+    /// Create the data from a stream of ballots that rank candidates by name
+    ///
+    /// Each candidate number is the index of their name in `candidate_order`, and the names are
+    /// also returned in that order so that results can be turned back into names, see
+    /// [decode_winners]. If a name is given more than once, the first one is used. Otherwise,
+    /// this is the same as [TabulatedData::from_ballots_iter].
+    ///
+    /// # Errors
+    /// [Error::InvalidCandidate] is returned for a name that isn't in `candidate_order`, with a
+    /// candidate number of the number of candidates. See [TabulatedData::from_ballots] for other
+    /// errors.
+    pub fn from_named_ballots<'a>(
+        ballots: impl IntoIterator<Item = Vec<&'a str>>,
+        candidate_order: &[&str],
+    ) -> Result<(Self, Vec<String>), Error> {
+        let candidates =
+            Candidate::try_from(candidate_order.len()).map_err(|_| Error::TooManyCandidates)?;
+        let mut numbers = BTreeMap::new();
+        for (number, name) in (0..candidates).zip(candidate_order) {
+            numbers.entry(*name).or_insert(number);
+        }
+
+        let data = Self::from_ballots_iter(
+            ballots.into_iter().map(|ballot| {
+                ballot
+                    .iter()
+                    .map(|name| numbers.get(name).copied().unwrap_or(candidates))
+                    .collect::<Vec<_>>()
+            }),
+            candidates,
+        )?;
+        let names = candidate_order
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        Ok((data, names))
+    }
+
+    /// Create the data from a stream of ballots, reporting progress
+    ///
+    /// This is the same as [TabulatedData::from_ballots_iter], but `progress` is called with the
+    /// number of ballots counted so far after every [PROGRESS_INTERVAL] ballots, and once more
+    /// with the total at the end if that was not a multiple of the interval.
+    ///
+    /// # Errors
+    /// See [TabulatedData::from_ballots]. Progress is not reported for the invalid ballot.
+    pub fn from_ballots_with_progress<B: AsRef<[Candidate]>>(
+        ballots: impl IntoIterator<Item = B>,
+        candidates: Candidate,
+        mut progress: impl FnMut(usize),
+    ) -> Result<Self, Error> {
+        let mut tabulator = pairwise::Tabulator::new(candidates, TruncationMode::default());
+        let mut counted = 0;
+        for ballot in ballots {
+            tabulator.add(ballot.as_ref(), 1)?;
+            counted += 1;
+            if counted % PROGRESS_INTERVAL == 0 {
+                progress(counted);
+            }
+        }
+
+        if counted % PROGRESS_INTERVAL != 0 {
+            progress(counted);
+        }
+
+        Ok(Self::from_counts(tabulator.finish()))
+    }
+
+    /// Create the data from a set of score ballots
+    ///
+    /// Each ballot maps candidates to their score. On each ballot, a candidate beats every
+    /// candidate with a lower score, as well as every candidate with no score (like an unranked
+    /// candidate on a ranked ballot). Candidates with equal scores are tied on that ballot.
+    ///
+    /// # Errors
+    /// An error will be returned if any ballot scores an invalid candidate number
+    /// (`>= candidates`).
+    pub fn from_score_ballots(
+        ballots: impl IntoIterator<Item = BTreeMap<Candidate, i32>>,
+        candidates: Candidate,
+    ) -> Result<Self, Error> {
+        let mut tabulator = pairwise::Tabulator::new(candidates, TruncationMode::default());
+        for ballot in ballots {
+            tabulator.add_scores(&ballot, 1)?;
+        }
+
+        Ok(Self::from_counts(tabulator.finish()))
+    }
+
+    /// Create the data from a set of approval ballots
+    ///
+    /// Each ballot is the set of candidates the voter approves of. On each ballot, every approved
+    /// candidate beats every candidate that isn't approved, and candidates are tied with everyone
+    /// else in the same group.
+    ///
+    /// # Errors
+    /// An error will be returned if any ballot approves an invalid candidate number
+    /// (`>= candidates`).
+    pub fn from_approval_ballots(
+        ballots: impl IntoIterator<Item = BTreeSet<Candidate>>,
+        candidates: Candidate,
+    ) -> Result<Self, Error> {
+        // an approval ballot is a score ballot where every approved candidate has the same score
+        Self::from_score_ballots(
+            ballots
+                .into_iter()
+                .map(|approved| approved.into_iter().map(|c| (c, 0)).collect()),
+            candidates,
+        )
+    }
+
+    /// Create the data from a table of pairwise results, keyed by margin of victory
+    ///
+    /// This is the inverse of [TabulatedData::into_pairwise_results]. Each pair is given as
+    /// `(winner, loser)`, and pairs that are not in the table are tied. Since there are no ballots,
+    /// the winner of each pair is counted as having `margin` votes and the loser none, see
+    /// [TabulatedData::pairwise_counts], and [TabulatedData::total_ballots] is zero.
+    ///
+    /// # Errors
+    /// [Error::InvalidTable] is returned if any pair has a margin of zero, has an invalid candidate
+    /// number (`>= candidates`), is a candidate against themselves, or is in the table more than
+    /// once in either order.
+    pub fn from_table(
+        table: BTreeMap<u64, BTreeSet<(Candidate, Candidate)>>,
+        candidates: Candidate,
+    ) -> Result<Self, Error> {
+        let mut seen = BTreeSet::new();
+        for (margin, pairings) in &table {
+            for (winner, loser) in pairings.iter().copied() {
+                if *margin == 0
+                    || winner >= candidates
+                    || loser >= candidates
+                    || winner == loser
+                    || !seen.insert((winner.min(loser), winner.max(loser)))
+                {
+                    return Err(Error::InvalidTable { winner, loser });
+                }
+            }
+        }
+
+        // an empty set would be locked in as a group with no pairings, so just remove them
+        let mut table = table;
+        table.retain(|_, pairings| !pairings.is_empty());
+
         Ok(Self {
-            table: pairwise::tabulate_pairwise_results(ballots, candidates)?,
+            counts: pairwise::PairwiseCounts::from_table(&table, candidates),
+            table,
             candidates,
         })
     }
 
+    /// Create the data from a set of ballots, each given as a comparison of pairs of candidates
+    ///
+    /// For each ballot, `compare(a, b)` should be [Ordering::Greater](core::cmp::Ordering::Greater)
+    /// if the voter prefers `a` over `b`, [Ordering::Less](core::cmp::Ordering::Less) if they prefer
+    /// `b`, and [Ordering::Equal](core::cmp::Ordering::Equal) if they have no preference. Each
+    /// comparison is only made once per pair of candidates, with `a < b`.
+    pub fn from_comparator_ballots(
+        ballots: impl IntoIterator<Item = impl Fn(Candidate, Candidate) -> core::cmp::Ordering>,
+        candidates: Candidate,
+    ) -> Self {
+        let mut tabulator = pairwise::Tabulator::new(candidates, TruncationMode::default());
+        for compare in ballots {
+            tabulator.add_comparator(compare, 1);
+        }
+
+        Self::from_counts(tabulator.finish())
+    }
+
+    /// Create the data from a set of ballots, grouping identical ballots together first
+    ///
+    /// This gives the same result as [TabulatedData::from_ballots], but each distinct ballot is
+    /// only counted once (with a weight), so it is faster when many ballots are the same. The
+    /// ballots are only iterated over once.
+    ///
+    /// # Errors
+    /// See [TabulatedData::from_ballots].
+    pub fn from_ballots_dedup<B: AsRef<[Candidate]>>(
+        ballots: impl IntoIterator<Item = B>,
+        candidates: Candidate,
+    ) -> Result<Self, Error> {
+        let mut weighted: BTreeMap<Vec<Candidate>, u64> = BTreeMap::new();
+        for (index, ballot) in ballots.into_iter().enumerate() {
+            let ballot = ballot.as_ref();
+            match weighted.get_mut(ballot) {
+                Some(weight) => *weight += 1,
+                // only check and copy the ballot the first time it is seen
+                None => {
+                    pairwise::check_ballot(index, ballot, candidates)?;
+                    weighted.insert(ballot.to_vec(), 1);
+                }
+            }
+        }
+
+        Self::from_weighted_ballots(weighted.iter().map(|(b, w)| (b, *w)), candidates)
+    }
+
     /// Tally election results
     ///
     /// The function returns the set of winners, using the algorithm in "Independence of clones as
     /// a criterion for voting rules" (Tideman, 1986). Specifically, as each winning margin is
     /// added to the graph, every possible order is considered. Any candidate who is can win in any
     /// scenario is considered to be in the winning set.
-    pub fn tally(&self) -> BTreeSet<u16> {
-        // create a graph
-        let mut graphs = HashSet::from([graph::AcyclicGraph::new(self.candidates)]);
-
-        // iterate over each group of equal-margin pairings, in order from largest margin of
-        // victory to smallest (reverse of usual order)
-        for pairings in self.pairwise_results() {
-            // get every possible ordering of the pairings with this margin of victory
-            let possible_match_orders = pairings.iter().copied().permutations(pairings.len());
-
-            // take every possible graph so far and modify it in each possible order
-            graphs = graphs
-                .into_iter()
-                .cartesian_product(possible_match_orders)
-                .map(|(mut graph, matches)| {
-                    for (winner, loser) in matches {
-                        // just skip adding if it would add a cycle
-                        graph.try_add_edge(winner, loser);
-                    }
-                    graph
-                })
-                .collect();
+    ///
+    /// With no candidates there are no winners, and with one candidate they always win, see
+    /// [TabulatedData::is_trivial].
+    pub fn tally(&self) -> BTreeSet<Candidate> {
+        if self.is_trivial() {
+            return (0..self.candidates).collect();
+        }
+
+        self.graphs()
+            .iter()
+            .flat_map(|graph| graph.roots())
+            .collect()
+    }
+
+    /// Tally election results, distinguishing a single winner from a tie
+    ///
+    /// This is the same as [TabulatedData::tally], see [TallyResult].
+    pub fn tally_result(&self) -> TallyResult {
+        let mut winners = self.tally();
+        match winners.len() {
+            0 => TallyResult::NoCandidates,
+            1 => TallyResult::Winner(winners.pop_first().unwrap()),
+            _ => TallyResult::Tie(winners),
         }
+    }
+
+    /// Get every candidate that can not win
+    ///
+    /// This is every candidate that is not in the set returned by [TabulatedData::tally], so it
+    /// is never a root of any possible final graph.
+    pub fn never_winners(&self) -> BTreeSet<Candidate> {
+        let winners = self.tally();
+        (0..self.candidates)
+            .filter(|c| !winners.contains(c))
+            .collect()
+    }
+
+    /// Get every candidate that can come last
+    ///
+    /// This is the reverse of [TabulatedData::tally]: any candidate who is a sink of the graph
+    /// (beats no one in it) in any scenario is considered to be in the losing set.
+    pub fn losers(&self) -> BTreeSet<Candidate> {
+        self.graphs()
+            .iter()
+            .flat_map(|graph| graph.sinks())
+            .collect()
+    }
+
+    /// Tally election results as if only the candidates in `include` had run
+    ///
+    /// This reuses the pairwise results, dropping every one that involves a candidate that is not
+    /// included. This gives the same result as tallying the ballots with every other candidate
+    /// removed from them. Candidates in `include` that are not in the election are ignored.
+    pub fn tally_subset(&self, include: &BTreeSet<Candidate>) -> BTreeSet<Candidate> {
+        let include: BTreeSet<Candidate> = include.range(..self.candidates).copied().collect();
+
+        let table: Vec<BTreeSet<(Candidate, Candidate)>> = self
+            .pairwise_results()
+            .map(|pairings| {
+                pairings
+                    .iter()
+                    .copied()
+                    .filter(|(w, l)| include.contains(w) && include.contains(l))
+                    .collect()
+            })
+            .filter(|pairings: &BTreeSet<_>| !pairings.is_empty())
+            .collect();
+
+        lock_all(AcyclicGraph::new(self.candidates), &table)
+            .iter()
+            .flat_map(|graph| graph.subgraph_roots(&include))
+            .collect()
+    }
+
+    /// Tally election results, grouping together candidates that are joined by pairwise ties
+    ///
+    /// This is the same as [TabulatedData::tally], except that tied pairwise elections are
+    /// locked in last, as weak edges that group candidates together rather than ordering them. A
+    /// candidate is a winner if their group is unbeaten, see [AcyclicGraph::roots_with_ties].
+    pub fn tally_with_ties(&self) -> BTreeSet<Candidate> {
+        let ties = self.counts.ties();
+        self.graphs()
+            .iter()
+            .flat_map(|graph| graph.roots_with_ties(&ties))
+            .collect()
+    }
+
+    /// Tally election results, using the given measure of the strength of each pairwise win
+    ///
+    /// This is the same as [TabulatedData::tally], except that the pairwise results are locked in
+    /// from strongest to weakest according to `strength`. [TabulatedData::tally] is the same as
+    /// using [Strength::Margin].
+    pub fn tally_with(&self, strength: Strength) -> BTreeSet<Candidate> {
+        let graphs = match strength {
+            Strength::Margin => self.graphs(),
+            _ => lock_all(
+                AcyclicGraph::new(self.candidates),
+                self.counts.tabulate(strength).values().rev(),
+            ),
+        };
 
         graphs.iter().flat_map(|graph| graph.roots()).collect()
     }
 
+    /// Tally election results, limiting the number of graphs that are considered
+    ///
+    /// This is the same as [TabulatedData::tally], except that it gives up if more than
+    /// `max_graphs` distinct graphs would need to be considered at any point. This bounds the
+    /// memory used for elections with many ties.
+    ///
+    /// # Errors
+    /// [Error::TooManyTiebreaks] is returned if the limit is exceeded.
+    pub fn tally_bounded(&self, max_graphs: usize) -> Result<BTreeSet<Candidate>, Error> {
+        Ok(lock(
+            AcyclicGraph::new(self.candidates),
+            self.pairwise_results(),
+            max_graphs,
+        )?
+        .iter()
+        .flat_map(|graph| graph.roots())
+        .collect())
+    }
+
+    /// Tally election results, with some pairings locked in before any others
+    ///
+    /// Each `(winner, loser)` pair in `forced` is locked in first, and then the pairwise results
+    /// are locked in as in [TabulatedData::tally]. Any result that conflicts with the forced
+    /// pairings is skipped, like any other result that would create a cycle.
+    ///
+    /// # Errors
+    /// [Error::InvalidConstraints] is returned if the forced pairings form a cycle, or have a
+    /// candidate that is not in the election.
+    pub fn tally_with_constraints(
+        &self,
+        forced: &[(Candidate, Candidate)],
+    ) -> Result<BTreeSet<Candidate>, Error> {
+        let start = AcyclicGraph::from_edges(self.candidates, forced.iter().copied())
+            .map_err(Error::InvalidConstraints)?;
+
+        Ok(lock_all(start, self.pairwise_results())
+            .iter()
+            .flat_map(|graph| graph.roots())
+            .collect())
+    }
+
+    /// Tally election results, limiting the number of graphs to [DEFAULT_MAX_GRAPHS]
+    ///
+    /// See [TabulatedData::tally_bounded].
+    ///
+    /// # Errors
+    /// [Error::TooManyTiebreaks] is returned if the limit is exceeded.
+    pub fn try_tally(&self) -> Result<BTreeSet<Candidate>, Error> {
+        self.tally_bounded(DEFAULT_MAX_GRAPHS)
+    }
+
+    /// Tally election results, only locking in pairwise wins with a margin of at least
+    /// `min_margin`
+    ///
+    /// This gives the provisional winners after locking in only the strongest wins. Every
+    /// candidate is a winner if no wins are strong enough, and with a `min_margin` of one or less
+    /// this is the same as [TabulatedData::tally].
+    pub fn tally_partial(&self, min_margin: u64) -> BTreeSet<Candidate> {
+        let groups = self
+            .table
+            .range(min_margin..)
+            .rev()
+            .map(|(_, pairings)| pairings);
+        lock_all(AcyclicGraph::new(self.candidates), groups)
+            .iter()
+            .flat_map(|graph| graph.roots())
+            .collect()
+    }
+
+    /// Tally the full election ranking
+    ///
+    /// The ranking is returned as a list of tiers, starting with the set of winners (the same set
+    /// returned by [TabulatedData::tally]). Each following tier is found by removing every
+    /// candidate in the tiers so far, and taking every candidate that is a root of the remaining
+    /// graph in any scenario. Candidates that can not be ordered relative to each other share a
+    /// tier.
+    pub fn tally_ranking(&self) -> Vec<BTreeSet<Candidate>> {
+        let graphs = self.graphs();
+
+        let mut remaining: BTreeSet<Candidate> = (0..self.candidates).collect();
+        let mut ranking = Vec::new();
+        while !remaining.is_empty() {
+            let tier: BTreeSet<Candidate> = graphs
+                .iter()
+                .flat_map(|graph| graph.subgraph_roots(&remaining))
+                .collect();
+            remaining.retain(|c| !tier.contains(c));
+            ranking.push(tier);
+        }
+
+        ranking
+    }
+
+    /// Get every candidate that can come second
+    ///
+    /// This removes the winners (the set returned by [TabulatedData::tally]) from every possible
+    /// final graph, and returns every candidate that is then a root in any scenario. This is the
+    /// second tier of [TabulatedData::tally_ranking], and is empty if every candidate can win.
+    pub fn runner_up(&self) -> BTreeSet<Candidate> {
+        let graphs = self.graphs();
+        let winners: BTreeSet<Candidate> = graphs.iter().flat_map(|graph| graph.roots()).collect();
+
+        let remaining = (0..self.candidates)
+            .filter(|c| !winners.contains(c))
+            .collect();
+        graphs
+            .iter()
+            .flat_map(|graph| graph.subgraph_roots(&remaining))
+            .collect()
+    }
+
+    /// Tally a single ranking that every possible final graph agrees on
+    ///
+    /// The ranking is returned as a list of tiers. A candidate is placed in an earlier tier than
+    /// another only if it is ranked above the other in every possible final graph (see
+    /// [TabulatedData::locked_graphs]), so candidates that are ordered differently in different
+    /// graphs share a tier.
+    pub fn consensus_ranking(&self) -> Vec<BTreeSet<Candidate>> {
+        let graphs = self.graphs();
+
+        // `above[a][b]` is true if `a` is ranked above `b` in every graph
+        let above: Vec<Vec<bool>> = (0..self.candidates)
+            .map(|a| {
+                (0..self.candidates)
+                    .map(|b| {
+                        a != b
+                            && graphs
+                                .iter()
+                                .all(|graph| graph.is_reachable(a, b) == Ok(true))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // candidates ranked above more others come first, this always puts `a` before `b` if `a`
+        // is above `b`, since `a` is then also above everything `b` is above
+        let order: Vec<Candidate> = (0..self.candidates)
+            .sorted_by_key(|a| {
+                core::cmp::Reverse(above[*a as usize].iter().filter(|x| **x).count())
+            })
+            .collect();
+
+        // start a new tier wherever everything so far is above everything after
+        let mut ranking = Vec::new();
+        let mut tier = BTreeSet::new();
+        for (i, candidate) in order.iter().enumerate() {
+            tier.insert(*candidate);
+            let split = order[..=i].iter().all(|a| {
+                order[i + 1..]
+                    .iter()
+                    .all(|b| above[*a as usize][*b as usize])
+            });
+            if split {
+                ranking.push(core::mem::take(&mut tier));
+            }
+        }
+
+        ranking
+    }
+
+    /// Get every possible final graph
+    ///
+    /// This locks in the pairwise results in every possible order, as in [TabulatedData::tally],
+    /// and returns each distinct graph that can result. An edge from `a` to `b` means that the
+    /// majority preferring `a` over `b` was locked in.
+    pub fn locked_graphs(&self) -> BTreeSet<AcyclicGraph> {
+        self.graphs().into_iter().collect()
+    }
+
+    /// Get the number of distinct possible final graphs
+    ///
+    /// This is the number of graphs returned by [TabulatedData::locked_graphs]. One means that
+    /// every order of locking in tied pairwise results gives the same graph, and larger values
+    /// mean the order matters more.
+    pub fn ambiguity(&self) -> usize {
+        self.graphs().len()
+    }
+
+    /// Get the margins of victory where locking in the tied pairwise wins added possible graphs
+    ///
+    /// This is every margin shared by pairwise wins that give more distinct graphs depending on
+    /// the order they are locked in, so these are the margins responsible for any ambiguity in the
+    /// result, see [TabulatedData::ambiguity]. The margins are given from widest to slimmest.
+    pub fn ambiguous_margins(&self) -> Vec<u64> {
+        let mut graphs = GraphSet::from([AcyclicGraph::new(self.candidates)]);
+        let mut margins = Vec::new();
+        for (margin, pairings) in self.pairwise_results_with_margin() {
+            let before = graphs.len();
+            graphs = lock_group_sequential(graphs, pairings, usize::MAX)
+                .expect("an unbounded set of graphs can not be too large");
+            if graphs.len() > before {
+                margins.push(margin);
+            }
+        }
+
+        margins
+    }
+
+    /// Get every possible final graph, see [TabulatedData::locked_graphs]
+    pub(crate) fn graphs(&self) -> GraphSet {
+        lock_all(AcyclicGraph::new(self.candidates), self.pairwise_results())
+    }
+
+    /// Get the data as if a candidate had never run
+    ///
+    /// This uses the counted pairwise results, so the ballots don't need to be read again. Every
+    /// candidate after `candidate` is numbered one lower, so with `n` candidates the result has
+    /// `n - 1`. The ballots that only ranked `candidate` are still counted in
+    /// [TabulatedData::total_ballots], but the other [TabulatedData::ballot_stats] depend on the
+    /// ballots themselves and are reset to zero.
+    ///
+    /// # Panics
+    /// Panics if `candidate` is not in the election.
+    pub fn without_candidate(&self, candidate: Candidate) -> TabulatedData {
+        assert!(candidate < self.candidates);
+
+        Self::from_counts(self.counts.without_candidate(candidate))
+    }
+
+    /// Get the number of candidates
+    pub fn candidates(&self) -> Candidate {
+        self.candidates
+    }
+
+    /// Check whether the result does not depend on the ballots
+    ///
+    /// This is true when there are zero or one candidates, where there is nothing to compare: the
+    /// winners are either nobody or the only candidate.
+    pub fn is_trivial(&self) -> bool {
+        self.candidates <= 1
+    }
+
+    /// Get the number of ballots counted
+    ///
+    /// For weighted ballots this is the total weight. Every ballot is counted, even ones that
+    /// don't rank any candidates.
+    pub fn total_ballots(&self) -> u64 {
+        self.counts.ballots()
+    }
+
     /// Get each set of non-tied pairwise elections
     ///
     /// The sets are of elections with the same margin of victory. The values are in order from
     /// widest margin of victory to slimmest.
-    pub fn pairwise_results(&self) -> impl Iterator<Item = &BTreeSet<(u16, u16)>> {
+    pub fn pairwise_results(&self) -> impl Iterator<Item = &BTreeSet<(Candidate, Candidate)>> {
         self.table.values().rev()
     }
+
+    /// Get each set of non-tied pairwise elections, along with their margin of victory
+    ///
+    /// This is the same as [TabulatedData::pairwise_results], with each set paired with the
+    /// margin of victory of every election in it.
+    pub fn pairwise_results_with_margin(
+        &self,
+    ) -> impl Iterator<Item = (u64, &BTreeSet<(Candidate, Candidate)>)> {
+        self.table
+            .iter()
+            .rev()
+            .map(|(margin, pairings)| (*margin, pairings))
+    }
+
+    /// Get each set of non-tied pairwise elections, from slimmest margin of victory to widest
+    ///
+    /// This is the same as [TabulatedData::pairwise_results], in the opposite order.
+    pub fn pairwise_results_ascending(
+        &self,
+    ) -> impl Iterator<Item = &BTreeSet<(Candidate, Candidate)>> {
+        self.table.values()
+    }
+
+    /// Get each set of non-tied pairwise elections, along with their margin of victory, from
+    /// slimmest margin of victory to widest
+    ///
+    /// This is the same as [TabulatedData::pairwise_results_with_margin], in the opposite order.
+    pub fn pairwise_results_with_margin_ascending(
+        &self,
+    ) -> impl Iterator<Item = (u64, &BTreeSet<(Candidate, Candidate)>)> {
+        self.table
+            .iter()
+            .map(|(margin, pairings)| (*margin, pairings))
+    }
+
+    /// Take the table of non-tied pairwise elections, keyed by margin of victory
+    ///
+    /// Each set contains every pairwise election, as `(winner, loser)`, with that margin. This is
+    /// the same data as [TabulatedData::pairwise_results_with_margin], in ascending order.
+    pub fn into_pairwise_results(self) -> BTreeMap<u64, BTreeSet<(Candidate, Candidate)>> {
+        self.table
+    }
+
+    /// Take the table of non-tied pairwise elections, along with every tied pair
+    ///
+    /// The table is the same as [TabulatedData::into_pairwise_results], and every pair of
+    /// candidates that is not in it is tied. Each tied pair is given as `(a, b)` with `a < b`.
+    pub fn into_pairwise_results_with_ties(self) -> (BTreeMap<u64, Pairings>, Pairings) {
+        let ties = self.counts.ties();
+        (self.table, ties)
+    }
+
+    /// Get every pairwise majority, before any are skipped to avoid cycles
+    ///
+    /// Each item is `(winner, loser, margin)`. This is the full majority graph, which can
+    /// contain cycles. The items are in order from widest margin of victory to slimmest.
+    pub fn majority_graph(&self) -> Vec<(Candidate, Candidate, u64)> {
+        self.pairwise_results_with_margin()
+            .flat_map(|(margin, pairings)| {
+                pairings
+                    .iter()
+                    .map(move |(winner, loser)| (*winner, *loser, margin))
+            })
+            .collect()
+    }
+
+    /// Get the result of every pairwise election, including ties
+    ///
+    /// Each item is `(a, b, comparison)`, with one item for each pair of candidates in order. For
+    /// a win `a` is the winner, and otherwise `a` is the lower-numbered candidate.
+    pub fn all_pairwise(&self) -> Vec<(Candidate, Candidate, Comparison)> {
+        (0..self.candidates)
+            .tuple_combinations()
+            .map(|(a, b)| match self.counts.margin(a, b) {
+                0 => (a, b, Comparison::Tie),
+                margin if margin > 0 => (a, b, Comparison::Win(margin as u64)),
+                margin => (b, a, Comparison::Win(margin.unsigned_abs())),
+            })
+            .collect()
+    }
+
+    /// Get the result of the pairwise election between `a` and `b`
+    ///
+    /// This is [Ordering::Greater](core::cmp::Ordering::Greater) if more ballots rank `a` over `b`
+    /// than the reverse, [Ordering::Less](core::cmp::Ordering::Less) if `b` wins, and
+    /// [Ordering::Equal](core::cmp::Ordering::Equal) for a tie.
+    ///
+    /// # Panics
+    /// Panics if either candidate is invalid (`>= candidates`).
+    pub fn pairwise(&self, a: Candidate, b: Candidate) -> core::cmp::Ordering {
+        assert!(a < self.candidates);
+        assert!(b < self.candidates);
+
+        self.counts.wins(a, b).cmp(&self.counts.wins(b, a))
+    }
+
+    /// Get the result of every pairwise election involving `candidate`
+    ///
+    /// Each other candidate is mapped to the result of `candidate` against them, as in
+    /// [TabulatedData::pairwise]. The map is empty if `candidate` is invalid (`>= candidates`).
+    pub fn head_to_head(&self, candidate: Candidate) -> BTreeMap<Candidate, core::cmp::Ordering> {
+        if candidate >= self.candidates {
+            return BTreeMap::new();
+        }
+
+        (0..self.candidates)
+            .filter(|other| *other != candidate)
+            .map(|other| (other, self.pairwise(candidate, other)))
+            .collect()
+    }
+
+    /// Get the raw results of every pairwise election
+    ///
+    /// Each key is a pair of candidates `(a, b)` with `a < b`, and each value is the number of
+    /// ballots ranking `a` over `b` and the number ranking `b` over `a`. Every pair is included,
+    /// even if it is tied.
+    pub fn pairwise_counts(&self) -> BTreeMap<(Candidate, Candidate), (u64, u64)> {
+        (0..self.candidates)
+            .tuple_combinations()
+            .map(|(a, b)| ((a, b), (self.counts.wins(a, b), self.counts.wins(b, a))))
+            .collect()
+    }
+
+    /// Get the margin of every pairwise election
+    ///
+    /// Entry `[a][b]` is the number of ballots ranking `a` over `b`, minus the number ranking `b`
+    /// over `a`. This is negative when `a` loses to `b`, and zero on the diagonal.
+    pub fn margin_matrix(&self) -> Vec<Vec<i64>> {
+        (0..self.candidates)
+            .map(|a| {
+                (0..self.candidates)
+                    .map(|b| self.counts.margin(a, b))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Check that every ballot is valid, without tabulating them
+///
+/// This does the same checks as [TabulatedData::from_ballots], but is much cheaper.
+///
+/// # Errors
+/// See [TabulatedData::from_ballots].
+pub fn validate_ballots<B: AsRef<[Candidate]>>(
+    ballots: impl IntoIterator<Item = B>,
+    candidates: Candidate,
+) -> Result<(), Error> {
+    ballots
+        .into_iter()
+        .enumerate()
+        .try_for_each(|(index, ballot)| pairwise::check_ballot(index, ballot.as_ref(), candidates))
+}
+
+/// The default limit on the number of graphs considered, see [TabulatedData::try_tally]
+///
+/// Elections without many tied pairwise results only ever need a handful of graphs, so this is
+/// only reached in pathological cases.
+pub const DEFAULT_MAX_GRAPHS: usize = 10_000;
+
+/// The most candidates that [TabulatedData::from_ballots_infer] will infer, and a reasonable
+/// limit for [TabulatedData::from_ballots_growing]
+///
+/// The pairwise results take `candidates^2` words, so this prevents a single mistyped candidate
+/// number from taking all of the memory.
+pub const MAX_INFERRED_CANDIDATES: Candidate = 4096;
+
+/// The fixed-point scale of fractional ballot weights, see
+/// [TabulatedData::from_weighted_ballots_f64]
+///
+/// A weight of one is counted as this value.
+pub const WEIGHT_SCALE: u64 = 1 << 20;
+
+/// How many ballots are counted between each progress report, see
+/// [TabulatedData::from_ballots_with_progress]
+pub const PROGRESS_INTERVAL: usize = 1000;
+
+/// The measure used to compare the strength of two pairwise wins
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Strength {
+    /// The number of ballots ranking the winner over the loser, minus the number ranking the
+    /// loser over the winner
+    #[default]
+    Margin,
+    /// The number of ballots ranking the winner over the loser
+    ///
+    /// This differs from [Strength::Margin] when some ballots do not rank both candidates.
+    WinningVotes,
+    /// The margin, as in [Strength::Margin], with wins that have the same margin compared by
+    /// winning votes, as in [Strength::WinningVotes]
+    ///
+    /// Only wins that are equal by both measures are treated as tied.
+    MarginThenWinningVotes,
+    /// The margin, as in [Strength::Margin], as a fraction of the ballots that rank at least one
+    /// of the two candidates
+    ///
+    /// This stops truncated ballots from weakening wins between candidates that are often left
+    /// unranked. The fraction is compared in fixed point, scaled by [STRENGTH_SCALE].
+    RelativeMargin,
+    /// The margin, as in [Strength::Margin], as a fraction of the votes for either candidate in
+    /// their pairwise election
+    ///
+    /// Unlike [Strength::RelativeMargin], ballots that tie the two candidates are not counted,
+    /// which includes ballots that only rank one of them with [TruncationMode::Ignore]. The
+    /// fraction is compared in fixed point, scaled by [STRENGTH_SCALE].
+    Ratio,
+}
+
+impl Strength {
+    /// Get a key for the strength of a win, given the votes for the winner and the loser, and
+    /// the number of ballots that rank either of them
+    ///
+    /// Stronger wins have larger keys, which are compared lexicographically.
+    fn key(self, winner_votes: u64, loser_votes: u64, relevant: u64) -> (u64, u64) {
+        let margin = winner_votes - loser_votes;
+        match self {
+            Strength::Margin => (margin, 0),
+            Strength::WinningVotes => (winner_votes, 0),
+            Strength::MarginThenWinningVotes => (margin, winner_votes),
+            Strength::RelativeMargin => (scaled_fraction(margin, relevant), 0),
+            Strength::Ratio => (scaled_fraction(margin, winner_votes + loser_votes), 0),
+        }
+    }
+}
+
+/// The fixed-point scale of fractional strengths, such as [Strength::Ratio]
+///
+/// A fraction of one is given as this value.
+pub const STRENGTH_SCALE: u64 = 1 << 32;
+
+/// Get `numerator / denominator` as a fixed-point number scaled by [STRENGTH_SCALE]
+///
+/// The numerator must be at most the denominator, which must not be zero.
+fn scaled_fraction(numerator: u64, denominator: u64) -> u64 {
+    (u128::from(numerator) * u128::from(STRENGTH_SCALE) / u128::from(denominator)) as u64
+}
+
+/// The winners of an election, see [TabulatedData::tally_result]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TallyResult {
+    /// There is a single winner
+    Winner(Candidate),
+    /// More than one candidate can win
+    Tie(BTreeSet<Candidate>),
+    /// There are no candidates, so no one can win
+    NoCandidates,
+}
+
+/// The result of a pairwise election, see [TabulatedData::all_pairwise]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Comparison {
+    /// The first candidate won by this margin
+    Win(u64),
+    /// Both candidates had the same number of votes
+    Tie,
+}
+
+/// How candidates that are not ranked on a ballot are compared
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TruncationMode {
+    /// A ballot only counts in a pairwise election if it ranks both candidates
+    Ignore,
+    /// Every ranked candidate beats every unranked candidate, and unranked candidates are tied
+    #[default]
+    RankedBeatsUnranked,
+}
+
+// a set of pairs of candidates
+type Pairings = BTreeSet<(Candidate, Candidate)>;
+
+// the set of distinct graphs while locking in pairs, which is only hashed when `std` is available
+#[cfg(feature = "std")]
+type GraphSet = std::collections::HashSet<AcyclicGraph>;
+#[cfg(not(feature = "std"))]
+type GraphSet = BTreeSet<AcyclicGraph>;
+
+/// Lock in each group of equal-strength pairings in every possible order, with no limit
+///
+/// See [lock].
+fn lock_all<'a>(
+    start: AcyclicGraph,
+    groups: impl IntoIterator<Item = &'a BTreeSet<(Candidate, Candidate)>>,
+) -> GraphSet {
+    lock(start, groups, usize::MAX).expect("an unbounded set of graphs can not be too large")
+}
+
+/// Lock in each group of equal-strength pairings in every possible order
+///
+/// The groups must be given from strongest to weakest, and are locked in on top of the edges
+/// already in `start`. Every distinct resulting graph is returned, or an error if there would ever
+/// be more than `max_graphs` of them.
+fn lock<'a>(
+    start: AcyclicGraph,
+    groups: impl IntoIterator<Item = &'a BTreeSet<(Candidate, Candidate)>>,
+    max_graphs: usize,
+) -> Result<GraphSet, Error> {
+    let mut graphs = GraphSet::from([start]);
+    if graphs.len() > max_graphs {
+        return Err(Error::TooManyTiebreaks);
+    }
+
+    // iterate over each group of equal-strength pairings, in order from strongest to weakest
+    for pairings in groups {
+        #[cfg(feature = "rayon")]
+        if graphs.len() > 1 || pairings.len() > 1 {
+            // there is more than one combination, so it can be worth doing them in parallel
+            graphs = lock_group_parallel(graphs, pairings, max_graphs)?;
+            continue;
+        }
+
+        graphs = lock_group_sequential(graphs, pairings, max_graphs)?;
+    }
+
+    Ok(graphs)
+}
+
+/// Modify each graph by locking in the pairings in every possible order
+///
+/// An error is returned as soon as there are more than `max_graphs` resulting graphs.
+fn lock_group_sequential(
+    graphs: GraphSet,
+    pairings: &BTreeSet<(Candidate, Candidate)>,
+    max_graphs: usize,
+) -> Result<GraphSet, Error> {
+    // get every possible ordering of the pairings with this strength
+    let possible_match_orders = pairings.iter().copied().permutations(pairings.len());
+
+    // take every possible graph so far and modify it in each possible order
+    let mut next = GraphSet::new();
+    for graph in graphs {
+        let closure = closure_for(&graph, pairings);
+        for matches in possible_match_orders.clone() {
+            let mut graph = graph.clone();
+            lock_in(&mut graph, closure.as_ref(), matches);
+
+            next.insert(graph);
+            if next.len() > max_graphs {
+                return Err(Error::TooManyTiebreaks);
+            }
+        }
+    }
+
+    Ok(next)
+}
+
+/// Get the transitive closure of a graph, if it is worth keeping while locking in `pairings`
+///
+/// Each cycle check without it searches the graph, so it is only kept when there are several
+/// pairings to lock in and the graph is small enough, see [graph::MAX_CLOSURE_NODES].
+fn closure_for(
+    graph: &AcyclicGraph,
+    pairings: &BTreeSet<(Candidate, Candidate)>,
+) -> Option<graph::Closure> {
+    (pairings.len() > 1 && graph.nodes() <= graph::MAX_CLOSURE_NODES)
+        .then(|| graph::Closure::new(graph))
+}
+
+/// Lock in the pairings in order, skipping any that would create a cycle
+///
+/// `closure` must be the transitive closure of `graph`, if it is given.
+fn lock_in(
+    graph: &mut AcyclicGraph,
+    closure: Option<&graph::Closure>,
+    pairings: impl IntoIterator<Item = (Candidate, Candidate)>,
+) {
+    match closure {
+        Some(closure) => {
+            let mut closure = closure.clone();
+            for (winner, loser) in pairings {
+                graph.try_add_edge_with(&mut closure, winner, loser);
+            }
+        }
+        None => {
+            for (winner, loser) in pairings {
+                graph.try_add_edge(winner, loser);
+            }
+        }
+    }
+}
+
+/// Modify each graph by locking in the pairings in every possible order, in parallel
+///
+/// This gives the same result as [lock_group_sequential].
+#[cfg(feature = "rayon")]
+fn lock_group_parallel(
+    graphs: GraphSet,
+    pairings: &BTreeSet<(Candidate, Candidate)>,
+    max_graphs: usize,
+) -> Result<GraphSet, Error> {
+    use rayon::prelude::*;
+
+    // get every possible ordering of the pairings with this strength
+    let possible_match_orders: Vec<Vec<(Candidate, Candidate)>> = pairings
+        .iter()
+        .copied()
+        .permutations(pairings.len())
+        .collect();
+
+    let check_size = |set: GraphSet| {
+        if set.len() > max_graphs {
+            Err(Error::TooManyTiebreaks)
+        } else {
+            Ok(set)
+        }
+    };
+
+    // take every possible graph so far and modify it in each possible order
+    graphs
+        .into_par_iter()
+        .flat_map_iter(|graph| {
+            let closure = closure_for(&graph, pairings);
+            possible_match_orders.iter().map(move |matches| {
+                let mut graph = graph.clone();
+                lock_in(&mut graph, closure.as_ref(), matches.iter().copied());
+                graph
+            })
+        })
+        .try_fold(GraphSet::new, |mut set, graph| {
+            set.insert(graph);
+            check_size(set)
+        })
+        .try_reduce(GraphSet::new, |mut a, b| {
+            a.extend(b);
+            check_size(a)
+        })
 }
 
 /// Tally election results
 ///
 /// This is a shortcut for [TabulatedData::from_ballots] followed by [TabulatedData::tally].
-pub fn tally<B: AsRef<[u16]>>(ballots: &[B], candidates: u16) -> Result<BTreeSet<u16>, Error> {
+pub fn tally<B: AsRef<[Candidate]>>(
+    ballots: &[B],
+    candidates: Candidate,
+) -> Result<BTreeSet<Candidate>, Error> {
     TabulatedData::from_ballots(ballots, candidates).map(|d| d.tally())
 }
 
+/// Rank every candidate in an election
+///
+/// This is a shortcut for [TabulatedData::from_ballots] followed by
+/// [TabulatedData::tally_ranking].
+pub fn rank<B: AsRef<[Candidate]>>(
+    ballots: &[B],
+    candidates: Candidate,
+) -> Result<Vec<BTreeSet<Candidate>>, Error> {
+    TabulatedData::from_ballots(ballots, candidates).map(|d| d.tally_ranking())
+}
+
+/// Count how often each candidate wins when the ballots are resampled
+///
+/// Each of the `samples` resamples draws as many ballots as there are, with replacement, and is
+/// tallied as in [tally]. A candidate is counted once for every resample that it wins, so with ties
+/// the counts add up to more than `samples`. Candidates that never win are left out.
+///
+/// # Errors
+/// See [TabulatedData::from_ballots].
+#[cfg(feature = "rand")]
+pub fn winner_distribution<B: AsRef<[Candidate]>>(
+    ballots: &[B],
+    candidates: Candidate,
+    samples: usize,
+    rng: &mut impl rand::Rng,
+) -> Result<BTreeMap<Candidate, usize>, Error> {
+    // check every ballot once, then each resample counts them by how often they were drawn
+    validate_ballots(ballots, candidates)?;
+
+    let mut wins = BTreeMap::new();
+    let mut weights = alloc::vec![0; ballots.len()];
+    for _ in 0..samples {
+        weights.fill(0);
+        for _ in 0..ballots.len() {
+            weights[rng.random_range(0..ballots.len())] += 1;
+        }
+
+        let resample = ballots
+            .iter()
+            .zip(&weights)
+            .filter(|(_, weight)| **weight > 0)
+            .map(|(ballot, weight)| (ballot.as_ref(), *weight));
+        for winner in TabulatedData::from_weighted_ballots(resample, candidates)?.tally() {
+            *wins.entry(winner).or_insert(0) += 1;
+        }
+    }
+
+    Ok(wins)
+}
+
+/// Get the label of each winner
+///
+/// The labels are indexed by candidate number, such as the names returned by
+/// [TabulatedData::from_named_ballots]. Winners without a label are skipped. The labels are given
+/// in the same order as the winners.
+pub fn decode_winners<'a>(winners: &BTreeSet<Candidate>, labels: &'a [String]) -> Vec<&'a str> {
+    winners
+        .iter()
+        .filter_map(|c| labels.get(*c as usize))
+        .map(String::as_str)
+        .collect()
+}
+
 /// An error while tallying an election
-#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq, Hash)]
 #[non_exhaustive]
 pub enum Error {
     /// A ballot had a duplicate choice
-    #[error("an invalid ballot was given")]
-    InvalidBallot,
+    #[error("ballot {ballot} has a duplicate choice")]
+    InvalidBallot {
+        /// The zero-based index of the ballot
+        ballot: usize,
+    },
     /// A ballot contained an invalid candidate number
-    #[error("an invalid candidate was voted for")]
-    InvalidCandidate,
+    #[error("ballot {ballot} voted for invalid candidate {candidate}")]
+    InvalidCandidate {
+        /// The zero-based index of the ballot
+        ballot: usize,
+        /// The invalid candidate number
+        candidate: Candidate,
+    },
+    /// A tie-break order was not an ordering of all of the candidates
+    #[error("an invalid tie-break order was given")]
+    InvalidTiebreak,
+    /// There were too many possible ways to break ties to consider all of them
+    #[error("too many possible tie-break orders")]
+    TooManyTiebreaks,
+    /// There were too many candidates, such as to consider every ordering of them
+    #[error("too many candidates")]
+    TooManyCandidates,
+    /// A table of pairwise results contained an invalid result
+    #[error("invalid pairwise result of {winner} over {loser}")]
+    InvalidTable {
+        /// The winner of the invalid result
+        winner: Candidate,
+        /// The loser of the invalid result
+        loser: Candidate,
+    },
+    /// A ballot had a negative, infinite, or too large weight
+    #[error("ballot {ballot} has an invalid weight")]
+    InvalidWeight {
+        /// The zero-based index of the ballot
+        ballot: usize,
+    },
+    /// Pairings that were required to be locked in formed a cycle, or had an invalid candidate
+    #[error("invalid forced pairings")]
+    InvalidConstraints(#[source] CycleError),
 }
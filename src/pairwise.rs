@@ -1,65 +1,431 @@
-use std::collections::{BTreeMap, BTreeSet};
-
-use crate::Error;
-
-pub fn tabulate_pairwise_results<B: AsRef<[u16]>>(
-    ballots: impl IntoIterator<Item = B> + Copy,
-    candidates: u16,
-) -> Result<BTreeMap<usize, BTreeSet<(u16, u16)>>, Error> {
-    for ballot in ballots {
-        check_ballot(ballot.as_ref(), candidates)?;
-    }
-
-    let mut pairwise_results: BTreeMap<usize, BTreeSet<(u16, u16)>> = BTreeMap::new();
-
-    if candidates < 2 {
-        // there are no pairs
-        return Ok(pairwise_results);
-    }
-
-    // iterate over each unique pairing
-    for c1 in 0..candidates - 1 {
-        for c2 in c1 + 1..candidates {
-            let (c1_wins, c2_wins) = count_pairwise_election(ballots, c1, c2);
-            match c1_wins.cmp(&c2_wins) {
-                // c1 won less than c2, so add c2 beating c1 by the margin
-                std::cmp::Ordering::Less => assert!(
-                    pairwise_results
-                        .entry(c2_wins - c1_wins)
-                        .or_default()
-                        .insert((c2, c1))
-                ),
-                std::cmp::Ordering::Equal => {
-                    // ties don't matter, so ignore
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec,
+    vec::Vec,
+};
+
+use itertools::Itertools as _;
+
+use crate::{Candidate, Error, Strength, TruncationMode};
+
+/// Adapter that gives every ballot a weight of one
+#[derive(Clone, Copy)]
+pub struct Unweighted<I>(pub I);
+
+impl<I: IntoIterator> IntoIterator for Unweighted<I> {
+    type Item = (I::Item, u64);
+    type IntoIter = core::iter::Zip<I::IntoIter, core::iter::Repeat<u64>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter().zip(core::iter::repeat(1))
+    }
+}
+
+/// The raw results of every pairwise election
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairwiseCounts {
+    candidates: Candidate,
+    // the number of ballots ranking `a` over `b` is at `a * candidates + b`
+    wins: Vec<u64>,
+    // the number of ballots ranking either `a` or `b`, for `a < b`, is at `a * candidates + b`
+    relevant: Vec<u64>,
+    // the total weight of every ballot counted
+    ballots: u64,
+    lengths: Lengths,
+}
+
+/// The total weight of ballots by how many candidates they rank
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Lengths {
+    /// Ballots ranking every candidate
+    pub full: u64,
+    /// Ballots ranking no candidates
+    pub empty: u64,
+    /// The number of candidates ranked on each ballot, added up
+    pub ranked: u64,
+}
+
+impl Lengths {
+    fn add(&mut self, ranked: usize, candidates: Candidate, weight: u64) {
+        if ranked == candidates as usize {
+            self.full += weight;
+        }
+        if ranked == 0 {
+            self.empty += weight;
+        }
+        self.ranked += ranked as u64 * weight;
+    }
+}
+
+impl PairwiseCounts {
+    /// Create counts where the winner of each pairing has `margin` votes, and the loser none
+    pub fn from_table(
+        table: &BTreeMap<u64, BTreeSet<(Candidate, Candidate)>>,
+        candidates: Candidate,
+    ) -> Self {
+        let size = candidates as usize;
+        let mut wins = vec![0; size * size];
+        let mut relevant = vec![0; size * size];
+        for (margin, pairings) in table {
+            for (winner, loser) in pairings {
+                wins[*winner as usize * size + *loser as usize] = *margin;
+                relevant[*winner.min(loser) as usize * size + *winner.max(loser) as usize] =
+                    *margin;
+            }
+        }
+
+        Self {
+            candidates,
+            wins,
+            relevant,
+            ballots: 0,
+            lengths: Lengths::default(),
+        }
+    }
+
+    pub fn candidates(&self) -> Candidate {
+        self.candidates
+    }
+
+    /// Get the total weight of every ballot counted
+    pub fn ballots(&self) -> u64 {
+        self.ballots
+    }
+
+    /// Get the total weight of ballots by how many candidates they rank
+    pub fn lengths(&self) -> Lengths {
+        self.lengths
+    }
+
+    /// Get the number of ballots that rank at least one of `a` and `b`
+    pub fn relevant(&self, a: Candidate, b: Candidate) -> u64 {
+        debug_assert!(a < self.candidates);
+        debug_assert!(b < self.candidates);
+
+        self.relevant[a.min(b) as usize * self.candidates as usize + a.max(b) as usize]
+    }
+
+    /// Get the number of ballots ranking `winner` over `loser`
+    pub fn wins(&self, winner: Candidate, loser: Candidate) -> u64 {
+        debug_assert!(winner < self.candidates);
+        debug_assert!(loser < self.candidates);
+
+        self.wins[winner as usize * self.candidates as usize + loser as usize]
+    }
+
+    /// Get the signed margin of `a` over `b`
+    pub fn margin(&self, a: Candidate, b: Candidate) -> i64 {
+        let a_wins = self.wins(a, b);
+        let b_wins = self.wins(b, a);
+        if a_wins >= b_wins {
+            (a_wins - b_wins) as i64
+        } else {
+            -((b_wins - a_wins) as i64)
+        }
+    }
+
+    /// Remove a candidate, numbering every later candidate one lower
+    ///
+    /// The number of candidates each ballot ranks without `removed` isn't known, so the lengths
+    /// are reset.
+    pub fn without_candidate(&self, removed: Candidate) -> Self {
+        let size = self.candidates as usize;
+        let kept: Vec<Candidate> = (0..self.candidates).filter(|c| *c != removed).collect();
+        let pick = |counts: &[u64]| {
+            kept.iter()
+                .flat_map(|a| {
+                    kept.iter()
+                        .map(move |b| counts[*a as usize * size + *b as usize])
+                })
+                .collect()
+        };
+
+        Self {
+            candidates: kept.len() as Candidate,
+            wins: pick(&self.wins),
+            relevant: pick(&self.relevant),
+            ballots: self.ballots,
+            lengths: Lengths::default(),
+        }
+    }
+
+    /// Get every tied pair, as `(a, b)` with `a < b`
+    pub fn ties(&self) -> BTreeSet<(Candidate, Candidate)> {
+        (0..self.candidates)
+            .tuple_combinations()
+            .filter(|(a, b)| self.wins(*a, *b) == self.wins(*b, *a))
+            .collect()
+    }
+
+    /// Group every non-tied pair by the strength of the win
+    ///
+    /// Each pair is given as `(winner, loser)`.
+    pub fn tabulate(
+        &self,
+        strength: Strength,
+    ) -> BTreeMap<(u64, u64), BTreeSet<(Candidate, Candidate)>> {
+        self.tabulate_pairs_by(|winner, loser| {
+            strength.key(
+                self.wins(winner, loser),
+                self.wins(loser, winner),
+                self.relevant(winner, loser),
+            )
+        })
+    }
+
+    /// Group every non-tied pair by a key, given the votes for the winner and the loser
+    ///
+    /// Each pair is given as `(winner, loser)`.
+    pub fn tabulate_by<K: Ord>(
+        &self,
+        key: impl Fn(u64, u64) -> K,
+    ) -> BTreeMap<K, BTreeSet<(Candidate, Candidate)>> {
+        self.tabulate_pairs_by(|winner, loser| {
+            key(self.wins(winner, loser), self.wins(loser, winner))
+        })
+    }
+
+    /// Group every non-tied pair by a key, given the winner and the loser
+    fn tabulate_pairs_by<K: Ord>(
+        &self,
+        key: impl Fn(Candidate, Candidate) -> K,
+    ) -> BTreeMap<K, BTreeSet<(Candidate, Candidate)>> {
+        let mut pairwise_results: BTreeMap<K, BTreeSet<(Candidate, Candidate)>> = BTreeMap::new();
+
+        // iterate over each unique pairing, so each is only inserted once
+        for (c1, c2) in (0..self.candidates).tuple_combinations() {
+            let c1_wins = self.wins(c1, c2);
+            let c2_wins = self.wins(c2, c1);
+            let pairing = match c1_wins.cmp(&c2_wins) {
+                // c1 won less than c2, so add c2 beating c1
+                core::cmp::Ordering::Less => (c2, c1),
+                // ties don't matter, so ignore
+                core::cmp::Ordering::Equal => continue,
+                // c1 won more than c2, so add c1 beating c2
+                core::cmp::Ordering::Greater => (c1, c2),
+            };
+            pairwise_results
+                .entry(key(pairing.0, pairing.1))
+                .or_default()
+                .insert(pairing);
+        }
+
+        pairwise_results
+    }
+}
+
+/// Accumulates pairwise results one ballot at a time
+pub struct Tabulator {
+    candidates: Candidate,
+    wins: Vec<u64>,
+    relevant: Vec<u64>,
+    // the number of ballots added so far, for errors
+    ballots: usize,
+    // the total weight of the ballots added so far
+    total: u64,
+    lengths: Lengths,
+    // scratch space for which candidates are ranked on the current ballot
+    ranked: Vec<bool>,
+    truncation: TruncationMode,
+}
+
+impl Tabulator {
+    pub fn new(candidates: Candidate, truncation: TruncationMode) -> Self {
+        let size = candidates as usize;
+        Self {
+            candidates,
+            wins: vec![0; size * size],
+            relevant: vec![0; size * size],
+            ballots: 0,
+            total: 0,
+            lengths: Lengths::default(),
+            ranked: vec![false; size],
+            truncation,
+        }
+    }
+
+    /// Check and count a single ballot
+    pub fn add(&mut self, ballot: &[Candidate], weight: u64) -> Result<(), Error> {
+        check_ballot(self.ballots, ballot, self.candidates)?;
+        self.ballots += 1;
+        self.total += weight;
+        self.lengths.add(ballot.len(), self.candidates, weight);
+
+        let size = self.candidates as usize;
+        for c in ballot {
+            self.ranked[*c as usize] = true;
+        }
+
+        for (i, winner) in ballot.iter().enumerate() {
+            let row = &mut self.wins[*winner as usize * size..][..size];
+
+            // each candidate beats every candidate ranked after them
+            for loser in &ballot[i + 1..] {
+                row[*loser as usize] += weight;
+            }
+
+            // and every candidate not ranked at all
+            if self.truncation == TruncationMode::RankedBeatsUnranked {
+                for (loser, ranked) in self.ranked.iter().enumerate() {
+                    if !ranked {
+                        row[loser] += weight;
+                    }
                 }
-                // c1 won more than c2, so add c1 beating c2 by the margin
-                std::cmp::Ordering::Greater => assert!(
-                    pairwise_results
-                        .entry(c1_wins - c2_wins)
-                        .or_default()
-                        .insert((c1, c2))
-                ),
             }
         }
+
+        self.count_relevant(ballot, weight);
+        for c in ballot {
+            self.ranked[*c as usize] = false;
+        }
+
+        Ok(())
     }
 
-    Ok(pairwise_results)
+    /// Check and count a single score ballot
+    pub fn add_scores(
+        &mut self,
+        scores: &BTreeMap<Candidate, i32>,
+        weight: u64,
+    ) -> Result<(), Error> {
+        if let Some(candidate) = scores.keys().copied().find(|c| *c >= self.candidates) {
+            return Err(Error::InvalidCandidate {
+                ballot: self.ballots,
+                candidate,
+            });
+        }
+        self.ballots += 1;
+        self.total += weight;
+        self.lengths.add(scores.len(), self.candidates, weight);
+
+        let size = self.candidates as usize;
+        for c in scores.keys() {
+            self.ranked[*c as usize] = true;
+        }
+
+        for (winner, winner_score) in scores {
+            let row = &mut self.wins[*winner as usize * size..][..size];
+
+            // each candidate beats every candidate with a lower score
+            for (loser, loser_score) in scores {
+                if winner_score > loser_score {
+                    row[*loser as usize] += weight;
+                }
+            }
+
+            // and every candidate with no score at all
+            if self.truncation == TruncationMode::RankedBeatsUnranked {
+                for (loser, ranked) in self.ranked.iter().enumerate() {
+                    if !ranked {
+                        row[loser] += weight;
+                    }
+                }
+            }
+        }
+
+        self.count_relevant(scores.keys(), weight);
+        for c in scores.keys() {
+            self.ranked[*c as usize] = false;
+        }
+
+        Ok(())
+    }
+
+    /// Count a single ballot, given as a comparison of pairs of candidates
+    ///
+    /// `compare(a, b)` is [Ordering::Greater](core::cmp::Ordering::Greater) if `a` is preferred
+    /// over `b`. It is only called with `a < b`. The ballot is counted as ranking every
+    /// candidate, since it compares every pair.
+    pub fn add_comparator(
+        &mut self,
+        compare: impl Fn(Candidate, Candidate) -> core::cmp::Ordering,
+        weight: u64,
+    ) {
+        self.ballots += 1;
+        self.total += weight;
+        self.lengths
+            .add(self.candidates as usize, self.candidates, weight);
+
+        let size = self.candidates as usize;
+        for (a, b) in (0..self.candidates).tuple_combinations() {
+            self.relevant[a as usize * size + b as usize] += weight;
+            match compare(a, b) {
+                core::cmp::Ordering::Greater => self.wins[a as usize * size + b as usize] += weight,
+                core::cmp::Ordering::Equal => {}
+                core::cmp::Ordering::Less => self.wins[b as usize * size + a as usize] += weight,
+            }
+        }
+    }
+
+    /// Count every pair of candidates where at least one of them is in `ranked`, which must be
+    /// the candidates marked in `self.ranked`
+    fn count_relevant<'a>(&mut self, ranked: impl IntoIterator<Item = &'a Candidate>, weight: u64) {
+        let size = self.candidates as usize;
+        for a in ranked {
+            for b in 0..self.candidates {
+                // pairs where both are ranked are only counted from the smaller candidate
+                if b != *a && (!self.ranked[b as usize] || *a < b) {
+                    self.relevant[(*a).min(b) as usize * size + (*a).max(b) as usize] += weight;
+                }
+            }
+        }
+    }
+
+    pub fn finish(self) -> PairwiseCounts {
+        PairwiseCounts {
+            candidates: self.candidates,
+            wins: self.wins,
+            relevant: self.relevant,
+            ballots: self.total,
+            lengths: self.lengths,
+        }
+    }
+}
+
+pub fn count_pairwise_results<B: AsRef<[Candidate]>>(
+    ballots: impl IntoIterator<Item = (B, u64)> + Clone,
+    candidates: Candidate,
+    truncation: TruncationMode,
+) -> Result<PairwiseCounts, Error> {
+    // check every ballot before allocating anything, so that an invalid ballot can't make this
+    // run out of memory however many candidates there are
+    crate::validate_ballots(
+        ballots.clone().into_iter().map(|(ballot, _)| ballot),
+        candidates,
+    )?;
+
+    // then each ballot is counted in a single pass, marking the candidates it ranks and counting
+    // every pair from that
+    let mut tabulator = Tabulator::new(candidates, truncation);
+    for (ballot, weight) in ballots {
+        tabulator.add(ballot.as_ref(), weight)?;
+    }
+
+    Ok(tabulator.finish())
 }
 
-fn check_ballot(ballot: &[u16], candidates: u16) -> Result<(), Error> {
+/// Check that a ballot is valid, `index` is only used for the error
+pub fn check_ballot(
+    index: usize,
+    ballot: &[Candidate],
+    candidates: Candidate,
+) -> Result<(), Error> {
     // tracker for what candidates have been seen in this ballot
     let mut selections = rangemap::RangeSet::new();
 
     for v in ballot {
         if *v >= candidates {
             // invalid candidate number
-            return Err(Error::InvalidCandidate);
+            return Err(Error::InvalidCandidate {
+                ballot: index,
+                candidate: *v,
+            });
         }
 
-        let v = usize::from(*v);
+        let v = *v as usize;
         if selections.contains(&v) {
             // duplicate candidate number
-            return Err(Error::InvalidBallot);
+            return Err(Error::InvalidBallot { ballot: index });
         } else {
             // insert the candidate
             selections.insert(v..v + 1);
@@ -69,27 +435,29 @@ fn check_ballot(ballot: &[u16], candidates: u16) -> Result<(), Error> {
     Ok(())
 }
 
-fn count_pairwise_election<B: AsRef<[u16]>>(
-    ballots: impl IntoIterator<Item = B>,
-    c1: u16,
-    c2: u16,
-) -> (usize, usize) {
+/// Count a single pairwise election by looking through every ballot, this is used to check
+/// [count_pairwise_results]
+#[cfg(test)]
+fn count_pairwise_election<B: AsRef<[Candidate]>>(
+    ballots: impl IntoIterator<Item = (B, u64)>,
+    c1: Candidate,
+    c2: Candidate,
+    truncation: TruncationMode,
+) -> (u64, u64) {
     let mut c1_wins = 0;
     let mut c2_wins = 0;
-    for ballot in ballots {
-        match ballot
-            .as_ref()
-            .iter()
-            .copied()
-            .find(|e| *e == c1 || *e == c2)
-        {
-            // c1 was ranked before c2
-            Some(v) if v == c1 => c1_wins += 1,
-            // c2 was ranked before c1
-            Some(v) if v == c2 => c2_wins += 1,
-            // this shouldn't happen, since only values that are either c1 or c2 are found
-            Some(_) => unreachable!(),
-            // neither candidate was ranked on this ballot
+    for (ballot, weight) in ballots {
+        let ballot = ballot.as_ref();
+        if truncation == TruncationMode::Ignore && !(ballot.contains(&c1) && ballot.contains(&c2)) {
+            // only ballots that rank both candidates count
+            continue;
+        }
+
+        // the first of the candidates on the ballot is preferred, and if neither candidate was
+        // ranked then the ballot doesn't count
+        match ballot.iter().find(|e| **e == c1 || **e == c2) {
+            Some(v) if *v == c1 => c1_wins += weight,
+            Some(_) => c2_wins += weight,
             None => {}
         }
     }
@@ -100,9 +468,26 @@ fn count_pairwise_election<B: AsRef<[u16]>>(
 mod test {
     use std::collections::{BTreeMap, BTreeSet};
 
-    use super::{count_pairwise_election, tabulate_pairwise_results};
+    use itertools::Itertools as _;
+
+    use crate::{
+        Candidate, Error, Strength,
+        TruncationMode::{Ignore, RankedBeatsUnranked},
+    };
 
-    const BALLOTS: &[&[u16]] = &[
+    use super::{
+        Tabulator, Unweighted, check_ballot, count_pairwise_election, count_pairwise_results,
+    };
+
+    fn tabulate_pairwise_results<B: AsRef<[Candidate]>>(
+        ballots: impl IntoIterator<Item = B> + Copy,
+        candidates: Candidate,
+    ) -> Result<BTreeMap<u64, BTreeSet<(Candidate, Candidate)>>, Error> {
+        count_pairwise_results(Unweighted(ballots), candidates, RankedBeatsUnranked)
+            .map(|counts| counts.tabulate_by(|winner, loser| winner - loser))
+    }
+
+    const BALLOTS: &[&[Candidate]] = &[
         [0, 1, 2].as_slice(),
         [1, 0, 2].as_slice(),
         [1, 2, 0].as_slice(),
@@ -112,21 +497,202 @@ mod test {
 
     #[test]
     fn count() {
-        assert_eq!(count_pairwise_election(BALLOTS, 0, 1), (1, 3));
-        assert_eq!(count_pairwise_election(BALLOTS, 1, 0), (3, 1));
-        assert_eq!(count_pairwise_election(BALLOTS, 0, 4), (3, 1));
-        assert_eq!(count_pairwise_election(BALLOTS, 4, 1), (1, 4));
-        assert_eq!(count_pairwise_election(BALLOTS, 2, 0), (1, 2));
-        assert_eq!(count_pairwise_election(BALLOTS, 4, 5), (1, 0));
-        assert_eq!(count_pairwise_election(BALLOTS, 8, 9), (0, 0));
+        assert_eq!(
+            count_pairwise_election(Unweighted(BALLOTS), 0, 1, RankedBeatsUnranked),
+            (1, 3)
+        );
+        assert_eq!(
+            count_pairwise_election(Unweighted(BALLOTS), 1, 0, RankedBeatsUnranked),
+            (3, 1)
+        );
+        assert_eq!(
+            count_pairwise_election(Unweighted(BALLOTS), 0, 4, RankedBeatsUnranked),
+            (3, 1)
+        );
+        assert_eq!(
+            count_pairwise_election(Unweighted(BALLOTS), 4, 1, RankedBeatsUnranked),
+            (1, 4)
+        );
+        assert_eq!(
+            count_pairwise_election(Unweighted(BALLOTS), 2, 0, RankedBeatsUnranked),
+            (1, 2)
+        );
+        assert_eq!(
+            count_pairwise_election(Unweighted(BALLOTS), 4, 5, RankedBeatsUnranked),
+            (1, 0)
+        );
+        assert_eq!(
+            count_pairwise_election(Unweighted(BALLOTS), 8, 9, RankedBeatsUnranked),
+            (0, 0)
+        );
 
-        assert_eq!(count_pairwise_election(BALLOTS, 1, 2), (4, 0));
+        assert_eq!(
+            count_pairwise_election(Unweighted(BALLOTS), 1, 2, RankedBeatsUnranked),
+            (4, 0)
+        );
+    }
+
+    #[test]
+    fn count_weighted() {
+        let weighted = [(BALLOTS[0], 3), (BALLOTS[1], 0), (BALLOTS[4], 2)];
+        assert_eq!(
+            count_pairwise_election(weighted, 0, 1, RankedBeatsUnranked),
+            (3, 0)
+        );
+        assert_eq!(
+            count_pairwise_election(weighted, 0, 4, RankedBeatsUnranked),
+            (3, 2)
+        );
+        assert_eq!(
+            count_pairwise_election(weighted, 4, 5, RankedBeatsUnranked),
+            (2, 0)
+        );
+    }
+
+    #[test]
+    fn count_ignore_unranked() {
+        assert_eq!(
+            count_pairwise_election(Unweighted(BALLOTS), 0, 1, Ignore),
+            (1, 2)
+        );
+        assert_eq!(
+            count_pairwise_election(Unweighted(BALLOTS), 0, 4, Ignore),
+            (0, 0)
+        );
+        assert_eq!(
+            count_pairwise_election(Unweighted(BALLOTS), 2, 0, Ignore),
+            (1, 2)
+        );
+        assert_eq!(
+            count_pairwise_election(Unweighted(BALLOTS), 1, 2, Ignore),
+            (3, 0)
+        );
+
+        let counts = count_pairwise_results(Unweighted(BALLOTS), 6, Ignore).unwrap();
+        assert_eq!((counts.wins(0, 1), counts.wins(1, 0)), (1, 2));
+        assert_eq!((counts.wins(1, 4), counts.wins(4, 1)), (0, 0));
+    }
+
+    #[test]
+    fn single_pass() {
+        // the same as counting each pair separately
+        let per_pair = |ballots: &[(Vec<Candidate>, u64)], candidates, truncation| {
+            let mut expected = Tabulator::new(candidates, truncation).finish();
+            for (c1, c2) in (0..candidates).tuple_combinations() {
+                let (c1_wins, c2_wins) =
+                    count_pairwise_election(ballots.iter().cloned(), c1, c2, truncation);
+                let size = candidates as usize;
+                expected.wins[c1 as usize * size + c2 as usize] = c1_wins;
+                expected.wins[c2 as usize * size + c1 as usize] = c2_wins;
+            }
+            expected.ballots = ballots.iter().map(|(_, weight)| weight).sum();
+            expected
+        };
+
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            for truncation in [RankedBeatsUnranked, Ignore] {
+                let weighted: Vec<(Vec<Candidate>, u64)> = ballots
+                    .iter()
+                    .enumerate()
+                    .map(|(i, ballot)| (ballot.clone(), i as u64 % 3))
+                    .collect();
+                let unweighted = ballots.iter().map(|ballot| (ballot.clone(), 1)).collect();
+                for ballots in [unweighted, weighted] {
+                    let counts =
+                        count_pairwise_results(ballots.iter().cloned(), candidates, truncation)
+                            .unwrap();
+                    let expected = per_pair(&ballots, candidates, truncation);
+                    assert_eq!(counts.wins, expected.wins);
+                    assert_eq!(counts.ballots, expected.ballots);
+                    assert_eq!(
+                        counts.tabulate(Strength::Margin),
+                        expected.tabulate(Strength::Margin)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tabulator() {
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let mut tabulator = Tabulator::new(candidates, RankedBeatsUnranked);
+            for ballot in &ballots {
+                tabulator.add(ballot, 1).unwrap();
+            }
+            assert_eq!(
+                tabulator.finish(),
+                count_pairwise_results(Unweighted(&ballots), candidates, RankedBeatsUnranked)
+                    .unwrap()
+            );
+        }
+
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let mut tabulator = Tabulator::new(candidates, Ignore);
+            for ballot in &ballots {
+                tabulator.add(ballot, 1).unwrap();
+            }
+            assert_eq!(
+                tabulator.finish(),
+                count_pairwise_results(Unweighted(&ballots), candidates, Ignore).unwrap()
+            );
+        }
+
+        let mut tabulator = Tabulator::new(6, RankedBeatsUnranked);
+        for (ballot, weight) in [(BALLOTS[0], 3), (BALLOTS[1], 0), (BALLOTS[4], 2)] {
+            tabulator.add(ballot, weight).unwrap();
+        }
+        let counts = tabulator.finish();
+        assert_eq!((counts.wins(0, 1), counts.wins(1, 0)), (3, 0));
+        assert_eq!((counts.wins(0, 4), counts.wins(4, 0)), (3, 2));
+        assert_eq!((counts.wins(4, 5), counts.wins(5, 4)), (2, 0));
+
+        let mut tabulator = Tabulator::new(3, RankedBeatsUnranked);
+        tabulator.add(&[0, 1, 2], 1).unwrap();
+        assert_eq!(
+            tabulator.add(&[0, 3], 1),
+            Err(Error::InvalidCandidate {
+                ballot: 1,
+                candidate: 3
+            })
+        );
     }
 
     #[test]
     fn errors() {
         tabulate_pairwise_results([[1]], 1).unwrap_err();
         tabulate_pairwise_results([[0, 1, 0]], 2).unwrap_err();
+
+        assert_eq!(
+            tabulate_pairwise_results([[0, 1], [1, 0], [1, 2]], 2),
+            Err(Error::InvalidCandidate {
+                ballot: 2,
+                candidate: 2
+            })
+        );
+        assert_eq!(
+            tabulate_pairwise_results([[0, 1], [1, 1], [0, 0]], 2),
+            Err(Error::InvalidBallot { ballot: 1 })
+        );
+
+        // ballots with no weight are still checked
+        count_pairwise_results([([1], 0)], 1, RankedBeatsUnranked).unwrap_err();
+    }
+
+    #[test]
+    fn wide_candidates() {
+        check_ballot(0, &[70_000, 0, 65_535], 70_001).unwrap();
+        assert_eq!(
+            check_ballot(0, &[70_000, 0, 70_001], 70_001),
+            Err(Error::InvalidCandidate {
+                ballot: 0,
+                candidate: 70_001
+            })
+        );
+        assert_eq!(
+            check_ballot(0, &[70_000, 0, 70_000], 70_001),
+            Err(Error::InvalidBallot { ballot: 0 })
+        );
     }
 
     #[test]
@@ -142,6 +708,25 @@ mod test {
         );
     }
 
+    #[test]
+    fn tideman_example_2_winning_votes() {
+        assert_eq!(
+            count_pairwise_results(
+                Unweighted(&crate::test::tideman_example_2_ballots()),
+                5,
+                RankedBeatsUnranked,
+            )
+            .unwrap()
+            .tabulate(Strength::WinningVotes),
+            BTreeMap::from([
+                ((33, 0), BTreeSet::from([(0, 2), (1, 2)])),
+                ((32, 0), BTreeSet::from([(2, 3), (2, 4)])),
+                ((31, 0), BTreeSet::from([(3, 0), (3, 1), (4, 0), (4, 1)])),
+                ((25, 0), BTreeSet::from([(0, 1), (3, 4)])),
+            ])
+        );
+    }
+
     #[test]
     fn tideman_example_3() {
         assert_eq!(
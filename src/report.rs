@@ -0,0 +1,863 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::{String, ToString as _},
+    vec,
+    vec::Vec,
+};
+
+use itertools::Itertools as _;
+
+use crate::{AcyclicGraph, Candidate, TabulatedData};
+
+/// A step-by-step explanation of a tally
+///
+/// See [TabulatedData::explain].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TallyReport {
+    /// Every pairwise win considered, in the order they were considered
+    pub steps: Vec<LockStep>,
+    /// The winners after every step
+    pub winners: BTreeSet<Candidate>,
+}
+
+/// A single pairwise win considered during a tally
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LockStep {
+    /// The winner of the pairwise election
+    pub winner: Candidate,
+    /// The loser of the pairwise election
+    pub loser: Candidate,
+    /// The margin of victory
+    pub margin: u64,
+    /// Whether the win was locked in, this is `false` if it would have created a cycle
+    pub locked: bool,
+}
+
+/// The result of a tally, along with some information about the election
+///
+/// See [TabulatedData::tally_detailed].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TallyOutcome {
+    /// The winners, the same as from [TabulatedData::tally]
+    pub winners: BTreeSet<Candidate>,
+    /// The Condorcet winner, if there was one
+    pub condorcet_winner: Option<Candidate>,
+    /// Whether any pairwise win was skipped because it would have created a cycle
+    pub cyclic: bool,
+}
+
+/// Which voting criteria the winners of an election satisfy
+///
+/// See [TabulatedData::criteria_report].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CriteriaReport {
+    /// Whether the Condorcet winner is the only winner, if there is a Condorcet winner
+    pub condorcet_winner_elected: Option<bool>,
+    /// Whether every winner is in the Smith set
+    pub winners_in_smith_set: bool,
+    /// Whether the Condorcet loser is a winner, if there is a Condorcet loser
+    pub condorcet_loser_elected: Option<bool>,
+}
+
+/// Statistics about the ballots counted, for spotting problems with the data
+///
+/// See [TabulatedData::ballot_stats]. Weighted ballots are counted as many times as their weight.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BallotStats {
+    /// The number of ballots, the same as [TabulatedData::total_ballots]
+    pub ballots: u64,
+    /// The number of ballots that rank every candidate
+    pub fully_ranked: u64,
+    /// The number of ballots that don't rank any candidates
+    pub empty: u64,
+    /// The average number of candidates ranked on each ballot, or zero if there are no ballots
+    pub average_length: f64,
+}
+
+/// The winners and full ranking of an election, which can be displayed
+///
+/// See [TabulatedData::summary].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TallySummary {
+    /// The winners, the same as from [TabulatedData::tally]
+    pub winners: BTreeSet<Candidate>,
+    /// The full ranking, the same as from [TabulatedData::tally_ranking]
+    pub ranking: Vec<BTreeSet<Candidate>>,
+}
+
+impl core::fmt::Display for TallySummary {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.winners.len() {
+            0 => writeln!(f, "No winner")?,
+            1 => writeln!(f, "Winner: {}", self.winners.iter().join(", "))?,
+            _ => writeln!(f, "Tie between: {}", self.winners.iter().join(", "))?,
+        }
+
+        write!(f, "Ranking:")?;
+        for (place, tier) in self.ranking.iter().enumerate() {
+            write!(f, "\n  {}: {}", place + 1, tier.iter().join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TabulatedData {
+    /// Tally the election results into a summary that can be displayed
+    ///
+    /// See [TallySummary].
+    pub fn summary(&self) -> TallySummary {
+        let ranking = self.tally_ranking();
+        TallySummary {
+            winners: ranking.first().cloned().unwrap_or_default(),
+            ranking,
+        }
+    }
+
+    /// Tally election results, with some extra information about the election
+    ///
+    /// See [TallyOutcome].
+    pub fn tally_detailed(&self) -> TallyOutcome {
+        TallyOutcome {
+            winners: self.tally(),
+            condorcet_winner: self.condorcet_winner(),
+            cyclic: self.is_cyclic(),
+        }
+    }
+
+    /// Check which voting criteria the winners of [TabulatedData::tally] satisfy
+    ///
+    /// Ranked pairs always satisfies these criteria, so this is mostly useful for teaching. See
+    /// [CriteriaReport].
+    pub fn criteria_report(&self) -> CriteriaReport {
+        let winners = self.tally();
+        CriteriaReport {
+            condorcet_winner_elected: self
+                .condorcet_winner()
+                .map(|c| winners == BTreeSet::from([c])),
+            winners_in_smith_set: winners.is_subset(&self.smith_set()),
+            condorcet_loser_elected: self.condorcet_loser().map(|c| winners.contains(&c)),
+        }
+    }
+
+    /// Get statistics about the ballots counted
+    ///
+    /// These are collected while the ballots are counted, so they are all zero for
+    /// [TabulatedData::from_table]. Ballots given as comparisons, for
+    /// [TabulatedData::from_comparator_ballots], are counted as ranking every candidate. See
+    /// [BallotStats].
+    pub fn ballot_stats(&self) -> BallotStats {
+        let ballots = self.counts.ballots();
+        let lengths = self.counts.lengths();
+        BallotStats {
+            ballots,
+            fully_ranked: lengths.full,
+            empty: lengths.empty,
+            average_length: if ballots == 0 {
+                0.0
+            } else {
+                lengths.ranked as f64 / ballots as f64
+            },
+        }
+    }
+
+    /// Format the margin matrix as a table, see [TabulatedData::pairwise_table_string_with_labels]
+    ///
+    /// Each candidate is labeled with its number.
+    pub fn pairwise_table_string(&self) -> String {
+        self.pairwise_table_string_with_labels(|candidate| candidate.to_string())
+    }
+
+    /// Format the margin matrix as a table, using the given candidate labels
+    ///
+    /// Each row gives the margins of its candidate over the candidate in each column, as in
+    /// [TabulatedData::margin_matrix]. Nonzero margins are signed, the diagonal is `-`, and the
+    /// columns are right-aligned.
+    pub fn pairwise_table_string_with_labels(&self, label: impl Fn(Candidate) -> String) -> String {
+        use core::fmt::Write as _;
+
+        let labels: Vec<String> = (0..self.candidates).map(label).collect();
+        let cells: Vec<Vec<String>> = self
+            .margin_matrix()
+            .into_iter()
+            .enumerate()
+            .map(|(a, row)| {
+                row.into_iter()
+                    .enumerate()
+                    .map(|(b, margin)| match margin {
+                        _ if a == b => "-".to_string(),
+                        0 => "0".to_string(),
+                        _ => format!("{margin:+}"),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let width = labels
+            .iter()
+            .chain(cells.iter().flatten())
+            .map(|cell| cell.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut table = String::new();
+        write!(table, "{:width$}", "").unwrap();
+        for label in &labels {
+            write!(table, " {label:>width$}").unwrap();
+        }
+        table.push('\n');
+        for (label, row) in labels.iter().zip(&cells) {
+            write!(table, "{label:>width$}").unwrap();
+            for cell in row {
+                write!(table, " {cell:>width$}").unwrap();
+            }
+            table.push('\n');
+        }
+
+        table
+    }
+
+    /// Check whether any pairwise win is skipped because it would create a cycle
+    ///
+    /// This is exactly the case where ranked pairs differs from locking in every pairwise win.
+    pub fn is_cyclic(&self) -> bool {
+        // any cycle of wins has to be broken whatever order the wins are locked in, so a single
+        // graph is enough; and every win is either locked in or skipped, so a graph with fewer
+        // edges than there are wins had to skip one
+        let graph =
+            crate::tiebreak::lock_sorted(self.candidates, self.pairwise_results(), Ord::cmp);
+        let wins: usize = self.pairwise_results().map(|pairings| pairings.len()).sum();
+        graph.edge_count() < wins
+    }
+
+    /// Find every cycle of pairwise wins
+    ///
+    /// Each cycle is a list of candidates where each beats the next, and the last beats the
+    /// first, which is why ranked pairs has to skip some pairwise win. Tied pairwise elections are
+    /// not wins. Each cycle starts from its smallest candidate and is only given once, and the
+    /// cycles are sorted. The number of cycles can grow exponentially with the number of
+    /// candidates, see [TabulatedData::is_cyclic] to just check whether there are any.
+    pub fn cycles(&self) -> Vec<Vec<Candidate>> {
+        let mut beats = vec![Vec::new(); self.candidates as usize];
+        for (winner, loser) in self.pairwise_results().flatten() {
+            beats[*winner as usize].push(*loser);
+        }
+        for losers in &mut beats {
+            losers.sort_unstable();
+        }
+
+        let mut cycles = Vec::new();
+        let mut on_path = vec![false; self.candidates as usize];
+        for start in 0..self.candidates {
+            extend_cycles(&beats, &mut vec![start], &mut on_path, &mut cycles);
+        }
+
+        cycles.sort_unstable();
+        cycles
+    }
+
+    /// Estimate the number of ballots that would need to change to change the winners
+    ///
+    /// A locked-in pairwise win is load-bearing if it is the only win over its loser in the final
+    /// graph, since without it the loser would also be a winner. This finds the load-bearing win
+    /// with the smallest margin in any possible final graph, and returns the number of ballots
+    /// that would need to be reversed to make that pairwise election a tie (half the margin,
+    /// rounded up). This is an approximation, the winners could also change in other ways.
+    ///
+    /// Zero is returned if no win is load-bearing, such as when every candidate can already win.
+    pub fn winning_margin_slack(&self) -> u64 {
+        self.graphs()
+            .iter()
+            .flat_map(|graph| {
+                graph
+                    .edges()
+                    .filter(|(_, loser)| graph.edges().filter(|(_, l)| l == loser).count() == 1)
+                    .collect::<Vec<_>>()
+            })
+            .map(|(winner, loser)| self.counts.margin(winner, loser) as u64)
+            .min()
+            .map_or(0, |margin| margin.div_ceil(2))
+    }
+
+    /// Find every candidate that could win if some ballots were added
+    ///
+    /// Each extra ballot for a candidate raises their margin over every other candidate by one and
+    /// leaves every other pairwise election the same, as a ballot with only that candidate ranked
+    /// does. A candidate is included if `extra_ballots` of these would make them a winner, as in
+    /// [TabulatedData::tally], so every current winner is included. This is only a bound, since
+    /// other ballots could also change the winners by changing other pairwise elections.
+    pub fn reachable_winners(&self, extra_ballots: u64) -> BTreeSet<Candidate> {
+        (0..self.candidates)
+            .filter(|candidate| {
+                let votes = |winner, loser| {
+                    let extra = if winner == *candidate {
+                        extra_ballots
+                    } else {
+                        0
+                    };
+                    self.counts.wins(winner, loser).saturating_add(extra)
+                };
+
+                // the pairwise results with the extra ballots
+                let mut table: BTreeMap<u64, BTreeSet<(Candidate, Candidate)>> = BTreeMap::new();
+                for (a, b) in (0..self.candidates).tuple_combinations() {
+                    let (a_votes, b_votes) = (votes(a, b), votes(b, a));
+                    if a_votes > b_votes {
+                        table.entry(a_votes - b_votes).or_default().insert((a, b));
+                    } else if b_votes > a_votes {
+                        table.entry(b_votes - a_votes).or_default().insert((b, a));
+                    }
+                }
+
+                crate::lock_all(AcyclicGraph::new(self.candidates), table.values().rev())
+                    .iter()
+                    .any(|graph| graph.roots().any(|root| root == *candidate))
+            })
+            .collect()
+    }
+
+    /// Get the margin of each locked-in pairwise win as a percentage of the ballots counted
+    ///
+    /// Each win is given as `(winner, loser, percentage)`, in the order they are locked in by
+    /// [TabulatedData::explain]. If no ballots were counted (as for
+    /// [TabulatedData::from_table]) this is empty.
+    pub fn locked_margin_percentages(&self) -> Vec<(Candidate, Candidate, f64)> {
+        let total = self.total_ballots();
+        if total == 0 {
+            return Vec::new();
+        }
+
+        self.explain()
+            .steps
+            .into_iter()
+            .filter(|step| step.locked)
+            .map(|step| {
+                let percentage = 100.0 * step.margin as f64 / total as f64;
+                (step.winner, step.loser, percentage)
+            })
+            .collect()
+    }
+
+    /// Explain the tally, step by step
+    ///
+    /// Each pairwise win is considered from widest margin of victory to slimmest, and is locked in
+    /// unless that would create a cycle. Wins with the same margin are considered in the same
+    /// canonical order as [TabulatedData::tally_deterministic], so when there are ties the winners
+    /// may be a subset of the winners from [TabulatedData::tally].
+    pub fn explain(&self) -> TallyReport {
+        let mut graph = AcyclicGraph::new(self.candidates);
+
+        let mut steps = Vec::new();
+        for (margin, pairings) in self.pairwise_results_with_margin() {
+            for (winner, loser) in pairings.iter().copied() {
+                steps.push(LockStep {
+                    winner,
+                    loser,
+                    margin,
+                    locked: graph.try_add_edge(winner, loser),
+                });
+            }
+        }
+
+        TallyReport {
+            steps,
+            winners: graph.roots().collect(),
+        }
+    }
+
+    /// Get every pairwise win in the order it is considered, as `(winner, loser, locked)`
+    ///
+    /// The wins are in the same canonical order as [TabulatedData::explain], and `locked` is false
+    /// for each win that is skipped because it would create a cycle.
+    pub fn lock_order(&self) -> Vec<(Candidate, Candidate, bool)> {
+        self.explain()
+            .steps
+            .into_iter()
+            .map(|step| (step.winner, step.loser, step.locked))
+            .collect()
+    }
+
+    /// Get every pairwise win, as `(winner, loser)`, that is skipped because it would create a
+    /// cycle
+    ///
+    /// The wins are locked in using the same canonical order as [TabulatedData::explain].
+    pub fn overruled_pairs(&self) -> BTreeSet<(Candidate, Candidate)> {
+        self.explain()
+            .steps
+            .into_iter()
+            .filter(|step| !step.locked)
+            .map(|step| (step.winner, step.loser))
+            .collect()
+    }
+
+    /// Get the locked-in pairwise wins, as `(winner, loser, margin)`, that make `winner` win
+    ///
+    /// The wins are locked in using the same canonical order as [TabulatedData::explain]. This is
+    /// every locked-in win of `winner`, and for every win over `winner` that is skipped, the chain
+    /// of locked-in wins from `winner` to that candidate which caused it to be skipped. Each win
+    /// is given once, in the order it was locked in. If `winner` doesn't win in that order, this
+    /// is empty.
+    ///
+    /// # Panics
+    /// If `winner` is not a candidate.
+    pub fn winner_justification(&self, winner: Candidate) -> Vec<(Candidate, Candidate, u64)> {
+        assert!(winner < self.candidates);
+
+        let mut graph = AcyclicGraph::new(self.candidates);
+        let mut locked = Vec::new();
+        let mut justification = BTreeSet::new();
+        for (margin, pairings) in self.pairwise_results_with_margin() {
+            for (a, b) in pairings.iter().copied() {
+                if graph.try_add_edge(a, b) {
+                    if a == winner {
+                        justification.insert(locked.len());
+                    }
+                    locked.push((a, b, margin));
+                } else if b == winner {
+                    let path = graph.path(winner, a).expect("the win would create a cycle");
+                    justification.extend(path.iter().tuple_windows().map(|(src, dst)| {
+                        locked
+                            .iter()
+                            .position(|(a, b, _)| (a, b) == (src, dst))
+                            .expect("every edge was locked in")
+                    }));
+                }
+            }
+        }
+
+        if !graph.roots().contains(&winner) {
+            return Vec::new();
+        }
+        justification.into_iter().map(|i| locked[i]).collect()
+    }
+}
+
+/// Find every cycle that continues `path` and then returns to its first candidate, only visiting
+/// candidates larger than the first
+///
+/// `on_path` must be set for every candidate in `path` after the first.
+fn extend_cycles(
+    beats: &[Vec<Candidate>],
+    path: &mut Vec<Candidate>,
+    on_path: &mut [bool],
+    cycles: &mut Vec<Vec<Candidate>>,
+) {
+    let start = path[0];
+    let last = path[path.len() - 1];
+    for next in beats[last as usize].iter().copied() {
+        if next == start {
+            cycles.push(path.clone());
+        } else if next > start && !on_path[next as usize] {
+            on_path[next as usize] = true;
+            path.push(next);
+            extend_cycles(beats, path, on_path, cycles);
+            path.pop();
+            on_path[next as usize] = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use crate::{Candidate, TabulatedData};
+
+    use super::{BallotStats, CriteriaReport, LockStep};
+
+    fn step(winner: Candidate, loser: Candidate, margin: u64, locked: bool) -> LockStep {
+        LockStep {
+            winner,
+            loser,
+            margin,
+            locked,
+        }
+    }
+
+    #[test]
+    fn pairwise_table() {
+        let data = TabulatedData::from_weighted_ballots(
+            [([0, 1, 2].as_slice(), 12), ([2, 1].as_slice(), 3)],
+            3,
+        )
+        .unwrap();
+        assert_eq!(
+            data.pairwise_table_string(),
+            concat!(
+                "    0  1  2\n",
+                " 0  - +9 +9\n",
+                " 1 -9  - +9\n",
+                " 2 -9 -9  -\n",
+            )
+        );
+        assert_eq!(
+            data.pairwise_table_string_with_labels(|c| ["Alice", "Bob", "Carol"][c as usize].into()),
+            concat!(
+                "      Alice   Bob Carol\n",
+                "Alice     -    +9    +9\n",
+                "  Bob    -9     -    +9\n",
+                "Carol    -9    -9     -\n",
+            )
+        );
+
+        let data = TabulatedData::from_ballots([[0; 0]; 0], 0).unwrap();
+        assert_eq!(data.pairwise_table_string(), "\n");
+    }
+
+    #[test]
+    fn tideman_example_4() {
+        let report = TabulatedData::from_ballots(&crate::test::tideman_example_4_ballots(), 4)
+            .unwrap()
+            .explain();
+        assert_eq!(
+            report.steps,
+            [
+                step(1, 2, 13, true),
+                step(0, 1, 9, true),
+                step(2, 0, 5, false),
+                step(0, 3, 3, true),
+                step(1, 3, 3, true),
+                step(2, 3, 3, true),
+            ]
+        );
+        assert_eq!(report.winners, BTreeSet::from([0]));
+    }
+
+    #[test]
+    fn winners() {
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+            let report = data.explain();
+            assert!(report.winners.is_subset(&data.tally()));
+            assert_eq!(report.winners.first().copied(), data.tally_deterministic());
+        }
+    }
+
+    #[test]
+    fn detailed() {
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+            assert_eq!(data.tally_detailed().winners, data.tally());
+        }
+
+        // there is a cycle between 0, 1, and 2
+        let outcome = TabulatedData::from_ballots(&crate::test::tideman_example_4_ballots(), 4)
+            .unwrap()
+            .tally_detailed();
+        assert_eq!(outcome.winners, BTreeSet::from([0]));
+        assert_eq!(outcome.condorcet_winner, None);
+        assert!(outcome.cyclic);
+
+        let outcome = TabulatedData::from_ballots(&crate::test::tideman_example_3_ballots(), 3)
+            .unwrap()
+            .tally_detailed();
+        assert_eq!(outcome.winners, BTreeSet::from([2]));
+        assert_eq!(outcome.condorcet_winner, Some(2));
+        assert!(!outcome.cyclic);
+
+        // pairwise ties aren't cycles
+        let outcome = TabulatedData::from_ballots([[0, 1, 2], [1, 0, 2]], 3)
+            .unwrap()
+            .tally_detailed();
+        assert_eq!(outcome.winners, BTreeSet::from([0, 1]));
+        assert_eq!(outcome.condorcet_winner, None);
+        assert!(!outcome.cyclic);
+    }
+
+    #[test]
+    fn criteria_report() {
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let report = TabulatedData::from_ballots(&ballots, candidates)
+                .unwrap()
+                .criteria_report();
+            assert_ne!(report.condorcet_winner_elected, Some(false));
+            assert!(report.winners_in_smith_set);
+            assert_ne!(report.condorcet_loser_elected, Some(true));
+        }
+
+        // 2 is the Condorcet winner and 0 is the Condorcet loser
+        let report = TabulatedData::from_ballots(&crate::test::tideman_example_3_ballots(), 3)
+            .unwrap()
+            .criteria_report();
+        assert_eq!(
+            report,
+            CriteriaReport {
+                condorcet_winner_elected: Some(true),
+                winners_in_smith_set: true,
+                condorcet_loser_elected: Some(false),
+            }
+        );
+
+        // a cycle has neither
+        let report = TabulatedData::from_ballots([[0, 1, 2], [1, 2, 0], [2, 0, 1]], 3)
+            .unwrap()
+            .criteria_report();
+        assert_eq!(report.condorcet_winner_elected, None);
+        assert_eq!(report.condorcet_loser_elected, None);
+    }
+
+    #[test]
+    fn ballot_stats() {
+        let data = TabulatedData::from_ballots(
+            [
+                [0, 1, 2].as_slice(),
+                [2].as_slice(),
+                [].as_slice(),
+                [1, 0].as_slice(),
+            ],
+            3,
+        )
+        .unwrap();
+        assert_eq!(
+            data.ballot_stats(),
+            BallotStats {
+                ballots: 4,
+                fully_ranked: 1,
+                empty: 1,
+                average_length: 1.5,
+            }
+        );
+
+        let data =
+            TabulatedData::from_weighted_ballots([([0, 1].as_slice(), 3), ([].as_slice(), 1)], 2)
+                .unwrap();
+        assert_eq!(
+            data.ballot_stats(),
+            BallotStats {
+                ballots: 4,
+                fully_ranked: 3,
+                empty: 1,
+                average_length: 1.5,
+            }
+        );
+
+        let data = TabulatedData::from_table(data.into_pairwise_results(), 2).unwrap();
+        assert_eq!(
+            data.ballot_stats(),
+            BallotStats {
+                ballots: 0,
+                fully_ranked: 0,
+                empty: 0,
+                average_length: 0.0,
+            }
+        );
+    }
+
+    #[test]
+    fn overruled_pairs() {
+        // 3 & 4 beat 0 & 1, but that was overruled by the stronger cycle through 2
+        let data =
+            TabulatedData::from_ballots(&crate::test::tideman_example_2_ballots(), 5).unwrap();
+        assert_eq!(
+            data.overruled_pairs(),
+            BTreeSet::from([(3, 0), (3, 1), (4, 0), (4, 1)])
+        );
+
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+            assert_eq!(data.overruled_pairs().is_empty(), !data.is_cyclic());
+        }
+    }
+
+    #[test]
+    fn lock_order() {
+        let data = TabulatedData::from_ballots([[0, 1, 2], [1, 2, 0], [2, 0, 1]], 3).unwrap();
+        assert_eq!(
+            data.lock_order(),
+            [(0, 1, true), (1, 2, true), (2, 0, false)]
+        );
+
+        let data =
+            TabulatedData::from_ballots(&crate::test::tideman_example_2_ballots(), 5).unwrap();
+        let order = data.lock_order();
+        assert_eq!(order.len(), data.majority_graph().len());
+        assert_eq!(
+            order
+                .iter()
+                .filter(|(_, _, locked)| !locked)
+                .map(|(winner, loser, _)| (*winner, *loser))
+                .collect::<BTreeSet<_>>(),
+            data.overruled_pairs()
+        );
+    }
+
+    #[test]
+    fn winner_justification() {
+        // 2 beating 0 is skipped because of the chain through 1
+        let data =
+            TabulatedData::from_ballots(&crate::test::tideman_example_4_ballots(), 4).unwrap();
+        assert_eq!(
+            data.winner_justification(0),
+            [(1, 2, 13), (0, 1, 9), (0, 3, 3)]
+        );
+        assert!(data.winner_justification(1).is_empty());
+
+        let data = TabulatedData::from_ballots([[0, 1, 2], [1, 2, 0], [2, 0, 1]], 3).unwrap();
+        assert_eq!(data.winner_justification(0), [(0, 1, 1), (1, 2, 1)]);
+
+        // with no wins at all every candidate wins with no justification
+        let data = TabulatedData::from_ballots([[0; 0]; 0], 2).unwrap();
+        assert!(data.winner_justification(1).is_empty());
+    }
+
+    #[test]
+    fn locked_margin_percentages() {
+        let data =
+            TabulatedData::from_weighted_ballots([([0, 1, 2], 3), ([1, 2, 0], 1)], 3).unwrap();
+        assert_eq!(
+            data.locked_margin_percentages(),
+            [(1, 2, 100.0), (0, 1, 50.0), (0, 2, 50.0)]
+        );
+
+        // 2 beating 0 is skipped
+        let data = TabulatedData::from_ballots([[0, 1, 2], [1, 2, 0], [2, 0, 1]], 3).unwrap();
+        let third = 100.0 / 3.0;
+        assert_eq!(
+            data.locked_margin_percentages(),
+            [(0, 1, third), (1, 2, third)]
+        );
+
+        let data = TabulatedData::from_table(data.into_pairwise_results(), 3).unwrap();
+        assert!(data.locked_margin_percentages().is_empty());
+    }
+
+    #[test]
+    fn summary() {
+        let summary = TabulatedData::from_ballots(&crate::test::tideman_example_2_ballots(), 5)
+            .unwrap()
+            .summary();
+        assert_eq!(summary.winners, BTreeSet::from([0]));
+        assert_eq!(
+            summary.to_string(),
+            "Winner: 0\nRanking:\n  1: 0\n  2: 1\n  3: 2\n  4: 3\n  5: 4"
+        );
+
+        let summary = TabulatedData::from_ballots([[0, 1, 2], [2, 0, 1]], 3)
+            .unwrap()
+            .summary();
+        assert_eq!(summary.winners, BTreeSet::from([0, 2]));
+        assert_eq!(
+            summary.to_string(),
+            "Tie between: 0, 2\nRanking:\n  1: 0, 2\n  2: 1"
+        );
+
+        let summary = TabulatedData::from_ballots([[0; 0]; 0], 0)
+            .unwrap()
+            .summary();
+        assert_eq!(summary.to_string(), "No winner\nRanking:");
+    }
+
+    #[test]
+    fn reachable_winners() {
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+            assert_eq!(data.reachable_winners(0), data.tally());
+            assert_eq!(
+                data.reachable_winners(ballots.len() as u64 + 1),
+                (0..candidates).collect()
+            );
+        }
+
+        // 0 beats 1 by 3
+        let data = TabulatedData::from_weighted_ballots([([0, 1].as_slice(), 3)], 2).unwrap();
+        assert_eq!(data.reachable_winners(2), BTreeSet::from([0]));
+        assert_eq!(data.reachable_winners(3), BTreeSet::from([0, 1]));
+
+        // 1 beats 0 by 3, 2 beats 1 by 2, 0 beats 2 by 1
+        let data = TabulatedData::from_weighted_ballots(
+            [
+                ([0].as_slice(), 3),
+                ([1, 0, 2].as_slice(), 2),
+                ([2, 1].as_slice(), 4),
+            ],
+            3,
+        )
+        .unwrap();
+        assert_eq!(data.reachable_winners(0), BTreeSet::from([2]));
+        assert_eq!(data.reachable_winners(1), BTreeSet::from([0, 1, 2]));
+        assert_eq!(data.reachable_winners(u64::MAX), BTreeSet::from([0, 1, 2]));
+    }
+
+    #[test]
+    fn winning_margin_slack() {
+        // 1 is only beaten by 0, by a margin of 2
+        let data =
+            TabulatedData::from_ballots(&crate::test::tideman_example_2_ballots(), 5).unwrap();
+        assert_eq!(data.winning_margin_slack(), 1);
+
+        // 0 beats 1 by 3
+        let data = TabulatedData::from_weighted_ballots([([0, 1].as_slice(), 3)], 2).unwrap();
+        assert_eq!(data.winning_margin_slack(), 2);
+
+        // 2 is beaten by both 0 and 1, so only the win of 0 over 1 is load-bearing
+        let data = TabulatedData::from_weighted_ballots(
+            [([0, 1, 2].as_slice(), 8), ([1, 0, 2].as_slice(), 2)],
+            3,
+        )
+        .unwrap();
+        assert_eq!(data.winning_margin_slack(), 3);
+
+        let data = TabulatedData::from_ballots([[0, 1], [1, 0]], 2).unwrap();
+        assert_eq!(data.winning_margin_slack(), 0);
+    }
+
+    #[test]
+    fn cyclic() {
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+            let wins: usize = data.pairwise_results().map(|pairings| pairings.len()).sum();
+            assert_eq!(
+                data.is_cyclic(),
+                data.locked_graphs()
+                    .iter()
+                    .any(|graph| graph.edge_count() < wins)
+            );
+        }
+
+        let cycle = TabulatedData::from_ballots([[0, 1, 2], [1, 2, 0], [2, 0, 1]], 3).unwrap();
+        assert!(cycle.is_cyclic());
+        let no_cycle = TabulatedData::from_ballots([[0, 1, 2], [1, 2, 0]], 3).unwrap();
+        assert!(!no_cycle.is_cyclic());
+    }
+
+    #[test]
+    fn cycles() {
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+            assert_eq!(!data.cycles().is_empty(), data.is_cyclic());
+        }
+
+        let cycle = TabulatedData::from_ballots([[0, 1, 2], [1, 2, 0], [2, 0, 1]], 3).unwrap();
+        assert_eq!(cycle.cycles(), [[0, 1, 2]]);
+        let no_cycle = TabulatedData::from_ballots([[0, 1, 2], [1, 2, 0]], 3).unwrap();
+        assert!(no_cycle.cycles().is_empty());
+
+        // 3 beats 0 and is beaten by 2, on top of the cycle of 0, 1, and 2, and 1 and 3 are tied
+        let data = TabulatedData::from_table(
+            BTreeMap::from([
+                (1, BTreeSet::from([(3, 0), (2, 3)])),
+                (2, BTreeSet::from([(0, 1), (1, 2), (2, 0)])),
+            ]),
+            4,
+        )
+        .unwrap();
+        assert_eq!(data.cycles(), [vec![0, 1, 2], vec![0, 1, 2, 3]]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize() {
+        let report = TabulatedData::from_ballots([[0, 1]], 2).unwrap().explain();
+        assert_eq!(
+            serde_json::to_string(&report).unwrap(),
+            r#"{"steps":[{"winner":0,"loser":1,"margin":1,"locked":true}],"winners":[0]}"#
+        );
+    }
+}
@@ -1,13 +1,74 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
-use crate::Error;
+use itertools::Itertools as _;
 
-use super::tally;
+use crate::{
+    Candidate, CycleError, Error, Strength, TabulatedData, TallyResult, TruncationMode,
+    WEIGHT_SCALE,
+};
+
+use super::{decode_winners, tally, validate_ballots};
 
 #[test]
 fn invalid_ballots() {
-    assert_eq!(tally(&[[1, 2], [0, 3]], 3), Err(Error::InvalidCandidate));
-    assert_eq!(tally(&[[0, 1, 2], [0, 1, 0]], 3), Err(Error::InvalidBallot),);
+    assert_eq!(
+        tally(&[[1, 2], [0, 3]], 3),
+        Err(Error::InvalidCandidate {
+            ballot: 1,
+            candidate: 3
+        })
+    );
+    assert_eq!(
+        tally(&[[0, 1, 2], [0, 1, 0]], 3),
+        Err(Error::InvalidBallot { ballot: 1 }),
+    );
+
+    // errors from many tallies can be collected
+    let errors: std::collections::HashSet<Error> = [[0, 3], [0, 0], [3, 0]]
+        .iter()
+        .filter_map(|ballot| tally(&[ballot], 3).err())
+        .collect();
+    assert_eq!(errors.len(), 2);
+}
+
+#[test]
+fn candidates() {
+    for candidates in 0..3 {
+        assert_eq!(
+            TabulatedData::from_ballots([[0; 0]; 0], candidates)
+                .unwrap()
+                .candidates(),
+            candidates
+        );
+    }
+}
+
+#[test]
+fn validate() {
+    assert_eq!(validate_ballots([[0, 1, 2], [2, 1, 0]], 3), Ok(()));
+    assert_eq!(validate_ballots([[0u32; 0]; 0], 0), Ok(()));
+    assert_eq!(
+        validate_ballots([[1, 2], [0, 3]], 3),
+        Err(Error::InvalidCandidate {
+            ballot: 1,
+            candidate: 3
+        })
+    );
+    assert_eq!(
+        validate_ballots(
+            [[0, 1, 2].as_slice(), [1].as_slice(), [0, 1, 0].as_slice()],
+            3
+        ),
+        Err(Error::InvalidBallot { ballot: 2 })
+    );
+
+    // this gives the same result as tabulating
+    for ballots in [[[1, 2], [0, 3]], [[0, 1], [1, 1]], [[0, 1], [1, 2]]] {
+        assert_eq!(
+            validate_ballots(ballots, 3),
+            TabulatedData::from_ballots(ballots, 3).map(|_| ())
+        );
+    }
 }
 
 #[test]
@@ -34,6 +95,20 @@ fn basic() {
     }
 }
 
+#[test]
+fn trivial() {
+    for candidates in 0..2 {
+        let data = TabulatedData::from_ballots([[0; 0]; 0], candidates).unwrap();
+        assert!(data.is_trivial());
+        assert_eq!(data.tally(), (0..candidates).collect());
+        assert_eq!(data.locked_graphs().len(), 1);
+    }
+
+    let data = TabulatedData::from_ballots([[0; 0]; 0], 2).unwrap();
+    assert!(!data.is_trivial());
+    assert_eq!(data.tally(), BTreeSet::from([0, 1]));
+}
+
 #[test]
 fn simple() {
     assert_eq!(
@@ -75,6 +150,89 @@ fn wikipedia_example() {
     );
 }
 
+#[test]
+fn wikipedia_example_ranking() {
+    let ballots = [
+        std::iter::repeat_n([0, 1, 2, 3].as_slice(), 42),
+        std::iter::repeat_n([1, 2, 3, 0].as_slice(), 26),
+        std::iter::repeat_n([2, 3, 1, 0].as_slice(), 15),
+        std::iter::repeat_n([3, 2, 1, 0].as_slice(), 17),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+
+    assert_eq!(
+        TabulatedData::from_ballots(&ballots, 4)
+            .unwrap()
+            .tally_ranking(),
+        [
+            BTreeSet::from([1]),
+            BTreeSet::from([2]),
+            BTreeSet::from([3]),
+            BTreeSet::from([0]),
+        ],
+    );
+}
+
+#[test]
+fn rank() {
+    for (ballots, candidates) in tideman_examples() {
+        assert_eq!(
+            super::rank(&ballots, candidates).unwrap(),
+            TabulatedData::from_ballots(&ballots, candidates)
+                .unwrap()
+                .tally_ranking()
+        );
+    }
+
+    assert_eq!(
+        super::rank(&[[0, 1, 2], [2, 0, 1]], 3).unwrap(),
+        [BTreeSet::from([0, 2]), BTreeSet::from([1])]
+    );
+    assert_eq!(
+        super::rank(&[[0, 3]], 3),
+        Err(Error::InvalidCandidate {
+            ballot: 0,
+            candidate: 3
+        })
+    );
+}
+
+#[test]
+#[cfg(feature = "rand")]
+fn winner_distribution() {
+    use rand::SeedableRng as _;
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+
+    // a unanimous winner always wins
+    let ballots = [[0, 1, 2], [0, 2, 1], [0, 1, 2]];
+    assert_eq!(
+        super::winner_distribution(&ballots, 3, 50, &mut rng),
+        Ok(BTreeMap::from([(0, 50)]))
+    );
+
+    // a close election is sometimes won by either candidate, and sometimes tied
+    let ballots = [[0, 1], [0, 1], [0, 1], [1, 0], [1, 0]];
+    let wins = super::winner_distribution(&ballots, 2, 200, &mut rng).unwrap();
+    assert!(wins[&0] > wins[&1]);
+    assert!(wins[&1] > 0);
+    assert!(wins.values().sum::<usize>() >= 200);
+
+    assert_eq!(
+        super::winner_distribution(&[[0; 0]; 0], 2, 3, &mut rng),
+        Ok(BTreeMap::from([(0, 3), (1, 3)]))
+    );
+    assert_eq!(
+        super::winner_distribution(&[[0, 3]], 3, 10, &mut rng),
+        Err(Error::InvalidCandidate {
+            ballot: 0,
+            candidate: 3
+        })
+    );
+}
+
 #[test]
 fn simple_tie() {
     assert_eq!(
@@ -96,18 +254,449 @@ fn simple_tie() {
     );
 }
 
+#[test]
+fn tally_result() {
+    let data = TabulatedData::from_ballots(&tideman_example_3_ballots(), 3).unwrap();
+    assert_eq!(data.tally_result(), TallyResult::Winner(2));
+
+    let data = TabulatedData::from_ballots([[0, 1, 2], [2, 0, 1]], 3).unwrap();
+    assert_eq!(
+        data.tally_result(),
+        TallyResult::Tie(BTreeSet::from([0, 2]))
+    );
+
+    let data = TabulatedData::from_ballots([[0; 0]; 0], 1).unwrap();
+    assert_eq!(data.tally_result(), TallyResult::Winner(0));
+    let data = TabulatedData::from_ballots([[0; 0]; 0], 0).unwrap();
+    assert_eq!(data.tally_result(), TallyResult::NoCandidates);
+}
+
+#[test]
+fn never_winners() {
+    let data = TabulatedData::from_ballots(
+        [
+            std::iter::repeat_n([0, 2].as_slice(), 8),
+            std::iter::repeat_n([2, 3, 0, 1].as_slice(), 4),
+            std::iter::repeat_n([2, 3, 1].as_slice(), 2),
+            std::iter::repeat_n([3, 2].as_slice(), 2),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .as_slice(),
+        4,
+    )
+    .unwrap();
+    assert_eq!(data.never_winners(), BTreeSet::from([1, 3]));
+
+    let data = TabulatedData::from_ballots([[0; 0]; 0], 3).unwrap();
+    assert_eq!(data.never_winners(), BTreeSet::new());
+}
+
+#[test]
+fn losers() {
+    let data = TabulatedData::from_ballots([[0, 1, 2], [0, 1, 2], [1, 0, 2]], 3).unwrap();
+    assert_eq!(data.losers(), BTreeSet::from([2]));
+
+    // a cycle, where anyone can come last
+    let data = TabulatedData::from_ballots([[0, 1, 2], [1, 2, 0], [2, 0, 1]], 3).unwrap();
+    assert_eq!(data.losers(), BTreeSet::from([0, 1, 2]));
+
+    let data = TabulatedData::from_ballots([[0; 0]; 0], 3).unwrap();
+    assert_eq!(data.losers(), BTreeSet::from([0, 1, 2]));
+}
+
+#[test]
+fn tally_subset() {
+    for (ballots, candidates) in tideman_examples() {
+        let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+        assert_eq!(data.tally_subset(&(0..candidates).collect()), data.tally());
+
+        for include in (0..candidates).powerset().skip(1) {
+            let filtered = filter_ballots(ballots.clone(), |c| include.contains(&c));
+            assert_eq!(
+                data.tally_subset(&include.iter().copied().collect()),
+                tally(&filtered, candidates).unwrap(),
+            );
+        }
+    }
+
+    let data = TabulatedData::from_ballots(&tideman_example_2_ballots(), 5).unwrap();
+    assert_eq!(data.tally_subset(&BTreeSet::new()), BTreeSet::new());
+    assert_eq!(
+        data.tally_subset(&BTreeSet::from([3, 4, 7])),
+        BTreeSet::from([3])
+    );
+}
+
+#[test]
+fn infer_candidates() {
+    for (ballots, candidates) in tideman_examples() {
+        assert_eq!(
+            TabulatedData::from_ballots_infer(&ballots).unwrap(),
+            TabulatedData::from_ballots(&ballots, candidates).unwrap(),
+        );
+    }
+
+    let data = TabulatedData::from_ballots_infer([[0; 0]; 0]).unwrap();
+    assert_eq!(data.candidates(), 0);
+    let data = TabulatedData::from_ballots_infer([[0; 0]; 3]).unwrap();
+    assert_eq!(data.candidates(), 0);
+    let data = TabulatedData::from_ballots_infer(&[vec![2], vec![], vec![0, 6]]).unwrap();
+    assert_eq!(data.candidates(), 7);
+
+    assert_eq!(
+        TabulatedData::from_ballots_infer(&[vec![2, 0, 2]]),
+        Err(Error::InvalidBallot { ballot: 0 })
+    );
+    assert_eq!(
+        TabulatedData::from_ballots_infer(&[vec![2], vec![3, Candidate::MAX]]),
+        Err(Error::InvalidCandidate {
+            ballot: 1,
+            candidate: Candidate::MAX
+        })
+    );
+
+    // the first invalid ballot is reported
+    assert_eq!(
+        TabulatedData::from_ballots_infer(&[vec![Candidate::MAX], vec![Candidate::MAX]]),
+        Err(Error::InvalidCandidate {
+            ballot: 0,
+            candidate: Candidate::MAX
+        })
+    );
+    assert_eq!(
+        TabulatedData::from_ballots_infer(&[vec![1, 1], vec![Candidate::MAX]]),
+        Err(Error::InvalidBallot { ballot: 0 })
+    );
+
+    // a large candidate number is rejected instead of allocating for it
+    let max = crate::MAX_INFERRED_CANDIDATES;
+    assert_eq!(
+        TabulatedData::from_ballots_infer(&[vec![max]]),
+        Err(Error::InvalidCandidate {
+            ballot: 0,
+            candidate: max
+        })
+    );
+    assert_eq!(
+        TabulatedData::from_ballots_infer(&[vec![0], vec![4_000_000_000]]),
+        Err(Error::InvalidCandidate {
+            ballot: 1,
+            candidate: 4_000_000_000
+        })
+    );
+}
+
+#[test]
+fn growing_candidates() {
+    let max = crate::MAX_INFERRED_CANDIDATES;
+    let ballots = [vec![0, 1], vec![1, 4], vec![]];
+    let data = TabulatedData::from_ballots_growing(&ballots, 3, max).unwrap();
+    assert_eq!(data, TabulatedData::from_ballots(&ballots, 5).unwrap());
+
+    // the declared candidates are kept even when nobody votes for them, or they are past the limit
+    let data = TabulatedData::from_ballots_growing(&ballots, 8, max).unwrap();
+    assert_eq!(data.candidates(), 8);
+    let data = TabulatedData::from_ballots_growing(&ballots, 8, 6).unwrap();
+    assert_eq!(data.candidates(), 8);
+
+    let data = TabulatedData::from_ballots_growing(&ballots, 3, 5).unwrap();
+    assert_eq!(data.candidates(), 5);
+    assert_eq!(
+        TabulatedData::from_ballots_growing(&ballots, 3, 4),
+        Err(Error::InvalidCandidate {
+            ballot: 1,
+            candidate: 4
+        })
+    );
+
+    assert_eq!(
+        TabulatedData::from_ballots_growing(&[vec![1, 1], vec![4_000_000_000]], 3, max),
+        Err(Error::InvalidBallot { ballot: 0 })
+    );
+    assert_eq!(
+        TabulatedData::from_ballots_growing(&[vec![4_000_000_000]], 3, max),
+        Err(Error::InvalidCandidate {
+            ballot: 0,
+            candidate: 4_000_000_000
+        })
+    );
+    assert_eq!(
+        TabulatedData::from_ballots_growing(&[vec![Candidate::MAX]], 3, Candidate::MAX),
+        Err(Error::InvalidCandidate {
+            ballot: 0,
+            candidate: Candidate::MAX
+        })
+    );
+}
+
+#[test]
+fn margin_then_winning_votes() {
+    // a cycle where every margin is two, but with different numbers of winning votes
+    let data = TabulatedData::from_weighted_ballots_with(
+        [
+            ([0, 1].as_slice(), 4),
+            ([1, 0].as_slice(), 2),
+            ([1, 2].as_slice(), 3),
+            ([2, 1].as_slice(), 1),
+            ([2, 0].as_slice(), 2),
+        ],
+        3,
+        TruncationMode::Ignore,
+    )
+    .unwrap();
+    assert_eq!(data.tally(), BTreeSet::from([0, 1, 2]));
+    assert_eq!(
+        data.tally_with(Strength::MarginThenWinningVotes),
+        BTreeSet::from([0])
+    );
+
+    // when the margins differ, they are used first
+    let data = TabulatedData::from_weighted_ballots(
+        [
+            ([0].as_slice(), 3),
+            ([1, 0, 2].as_slice(), 2),
+            ([2, 1].as_slice(), 4),
+        ],
+        3,
+    )
+    .unwrap();
+    assert_eq!(
+        data.tally_with(Strength::MarginThenWinningVotes),
+        data.tally_with(Strength::Margin)
+    );
+}
+
+#[test]
+fn truncation() {
+    let ballots = [
+        [0, 1].as_slice(),
+        [0, 1].as_slice(),
+        [2].as_slice(),
+        [2].as_slice(),
+        [2].as_slice(),
+        [1, 2].as_slice(),
+    ];
+
+    let data =
+        TabulatedData::from_ballots_with(ballots, 3, TruncationMode::RankedBeatsUnranked).unwrap();
+    assert_eq!(data, TabulatedData::from_ballots(ballots, 3).unwrap());
+    assert_eq!(data.tally(), BTreeSet::from([2]));
+
+    // only the last ballot compares 2 with anyone
+    let data = TabulatedData::from_ballots_with(ballots, 3, TruncationMode::Ignore).unwrap();
+    assert_eq!(
+        data.pairwise_counts(),
+        BTreeMap::from([((0, 1), (2, 0)), ((0, 2), (0, 0)), ((1, 2), (1, 0))])
+    );
+    assert_eq!(data.tally(), BTreeSet::from([0]));
+}
+
+#[test]
+fn tally_with_ties() {
+    for (ballots, candidates) in tideman_examples() {
+        let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+        assert_eq!(data.tally_with_ties(), data.tally());
+    }
+
+    // 1 is tied with both 0 and 2, but 2 is not grouped with the winners since 0 beats it
+    let data = TabulatedData::from_ballots(&[vec![0, 2], vec![1]], 3).unwrap();
+    assert_eq!(data.tally(), BTreeSet::from([0, 1]));
+    assert_eq!(data.tally_with_ties(), BTreeSet::from([0, 1]));
+
+    // 0 is tied with 1, which is beaten by 2; 0 is also tied with 2, but that can not join them
+    // since 2 beats 1
+    let data = TabulatedData::from_ballots(&[vec![2, 1], vec![0]], 3).unwrap();
+    assert_eq!(data.tally(), BTreeSet::from([0, 2]));
+    assert_eq!(data.tally_with_ties(), BTreeSet::from([2]));
+
+    // 0 and 1 are tied, but 2 is beaten by both
+    let data = TabulatedData::from_ballots([[0, 1, 2], [1, 0, 2]], 3).unwrap();
+    assert_eq!(data.tally_with_ties(), BTreeSet::from([0, 1]));
+}
+
+#[test]
+fn margin_and_winning_votes() {
+    let data = TabulatedData::from_weighted_ballots(
+        [
+            ([0].as_slice(), 3),
+            ([1, 0, 2].as_slice(), 2),
+            ([2, 1].as_slice(), 4),
+        ],
+        3,
+    )
+    .unwrap();
+
+    // 1 beats 0 by 6 to 3, 2 beats 1 by 4 to 2, 0 beats 2 by 5 to 4
+    assert_eq!(data.tally(), BTreeSet::from([2]));
+    assert_eq!(data.tally_with(Strength::Margin), BTreeSet::from([2]));
+    assert_eq!(data.tally_with(Strength::WinningVotes), BTreeSet::from([1]));
+}
+
+#[test]
+fn constraints() {
+    let data = TabulatedData::from_weighted_ballots(
+        [
+            ([0].as_slice(), 3),
+            ([1, 0, 2].as_slice(), 2),
+            ([2, 1].as_slice(), 4),
+        ],
+        3,
+    )
+    .unwrap();
+
+    // 1 beats 0 by 3, 2 beats 1 by 2, 0 beats 2 by 1
+    assert_eq!(data.tally_with_constraints(&[]), Ok(data.tally()));
+    assert_eq!(data.tally_with_constraints(&[(2, 1)]), Ok(data.tally()));
+    // forcing 0 over 2 means 2 over 1 is skipped instead
+    assert_eq!(
+        data.tally_with_constraints(&[(0, 2)]),
+        Ok(BTreeSet::from([1]))
+    );
+    // forcing the weakest result against the majority
+    assert_eq!(
+        data.tally_with_constraints(&[(1, 2)]),
+        Ok(BTreeSet::from([1]))
+    );
+
+    assert_eq!(
+        data.tally_with_constraints(&[(0, 1), (1, 2), (2, 0)]),
+        Err(Error::InvalidConstraints(CycleError::Cycle {
+            src: 2,
+            dst: 0
+        }))
+    );
+    assert_eq!(
+        data.tally_with_constraints(&[(0, 3)]),
+        Err(Error::InvalidConstraints(CycleError::InvalidNode {
+            src: 0,
+            dst: 3
+        }))
+    );
+}
+
+#[test]
+fn relative_margin() {
+    let data = TabulatedData::from_weighted_ballots(
+        [
+            ([1, 0].as_slice(), 4),
+            ([2, 1].as_slice(), 6),
+            ([0].as_slice(), 7),
+        ],
+        3,
+    )
+    .unwrap();
+
+    // 1 beats 0 by 10 to 7 of 17, 0 beats 2 by 11 to 6 of 17, 2 beats 1 by 6 to 4 of 10
+    assert_eq!(data.counts.relevant(0, 1), 17);
+    assert_eq!(data.counts.relevant(2, 0), 17);
+    assert_eq!(data.counts.relevant(1, 2), 10);
+    assert_eq!(data.tally_with(Strength::Margin), BTreeSet::from([1]));
+    assert_eq!(
+        data.tally_with(Strength::RelativeMargin),
+        BTreeSet::from([0])
+    );
+}
+
+#[test]
+fn ratio() {
+    let data = TabulatedData::from_weighted_ballots(
+        [
+            ([1, 0].as_slice(), 4),
+            ([2, 1].as_slice(), 6),
+            ([0].as_slice(), 7),
+        ],
+        3,
+    )
+    .unwrap();
+
+    // 1 beats 0 by 10 to 7, 0 beats 2 by 11 to 6, 2 beats 1 by 6 to 4
+    assert_eq!(data.tally_with(Strength::Margin), BTreeSet::from([1]));
+    assert_eq!(data.tally_with(Strength::Ratio), BTreeSet::from([0]));
+
+    let data = TabulatedData::from_weighted_ballots_with(
+        [
+            ([0, 2].as_slice(), 9),
+            ([1, 2].as_slice(), 1),
+            ([0].as_slice(), 5),
+            ([2, 1, 0].as_slice(), 4),
+        ],
+        3,
+        TruncationMode::Ignore,
+    )
+    .unwrap();
+
+    // 1 beats 0 by 4 to 0, 0 beats 2 by 9 to 4, 2 beats 1 by 4 to 1, while 19, 19, and 14 ballots
+    // rank either candidate
+    assert_eq!(data.tally_with(Strength::Margin), BTreeSet::from([1]));
+    assert_eq!(data.tally_with(Strength::Ratio), BTreeSet::from([2]));
+    assert_eq!(
+        data.tally_with(Strength::RelativeMargin),
+        BTreeSet::from([0])
+    );
+}
+
 // Tideman examples from: Tideman, T.N. Independence of clones as a criterion for voting rules. Soc
 // Choice Welfare 4, 185–206 (1987). https://doi.org/10.1007/BF00433944
 
 /// Filter the ballots, keeping only candidates for which the function gives `true`
-fn filter_ballots<B: Into<Vec<u16>>>(ballots: Vec<B>, f: impl Fn(u16) -> bool) -> Vec<Vec<u16>> {
+fn filter_ballots<B: Into<Vec<Candidate>>>(
+    ballots: Vec<B>,
+    f: impl Fn(Candidate) -> bool,
+) -> Vec<Vec<Candidate>> {
     ballots
         .into_iter()
         .map(|b| b.into().into_iter().filter(|n| f(*n)).collect())
         .collect()
 }
 
-pub fn tideman_example_2_ballots() -> Vec<[u16; 5]> {
+#[test]
+fn without_candidate() {
+    for (ballots, candidates) in tideman_examples() {
+        let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+        for removed in 0..candidates {
+            // renumber the candidates after the removed one
+            let filtered: Vec<Vec<Candidate>> = filter_ballots(ballots.clone(), |c| c != removed)
+                .into_iter()
+                .map(|b| {
+                    b.into_iter()
+                        .map(|c| c - (c > removed) as Candidate)
+                        .collect()
+                })
+                .collect();
+            let expected = TabulatedData::from_ballots(&filtered, candidates - 1).unwrap();
+
+            let without = data.without_candidate(removed);
+            assert_eq!(without.candidates(), candidates - 1);
+            assert_eq!(without.total_ballots(), expected.total_ballots());
+            assert_eq!(without.pairwise_counts(), expected.pairwise_counts());
+            assert_eq!(without.tally(), expected.tally());
+            assert_eq!(
+                without.tally_with(Strength::RelativeMargin),
+                expected.tally_with(Strength::RelativeMargin)
+            );
+        }
+    }
+
+    // removing the winner of a table
+    let data = TabulatedData::from_table(
+        BTreeMap::from([
+            (2, BTreeSet::from([(0, 1), (0, 2)])),
+            (1, BTreeSet::from([(2, 1)])),
+        ]),
+        3,
+    )
+    .unwrap();
+    assert_eq!(data.tally(), BTreeSet::from([0]));
+    let without = data.without_candidate(0);
+    assert_eq!(
+        without.into_pairwise_results(),
+        BTreeMap::from([(1, BTreeSet::from([(1, 0)]))])
+    );
+}
+
+pub fn tideman_example_2_ballots() -> Vec<[Candidate; 5]> {
     [
         std::iter::repeat_n([0, 1, 2, 3, 4], 9),
         std::iter::repeat_n([1, 0, 2, 4, 3], 8),
@@ -151,7 +740,7 @@ fn tideman_example_2() {
     );
 }
 
-pub fn tideman_example_3_ballots() -> Vec<[u16; 3]> {
+pub fn tideman_example_3_ballots() -> Vec<[Candidate; 3]> {
     [
         std::iter::repeat_n([0, 1, 2], 3),
         std::iter::repeat_n([2, 1, 0], 2),
@@ -176,7 +765,7 @@ fn tideman_example_3() {
     );
 }
 
-pub fn tideman_example_4_ballots() -> Vec<[u16; 4]> {
+pub fn tideman_example_4_ballots() -> Vec<[Candidate; 4]> {
     [
         std::iter::repeat_n([0, 1, 2, 3], 6),
         std::iter::repeat_n([1, 2, 0, 3], 5),
@@ -199,7 +788,23 @@ fn tideman_example_4() {
     );
 }
 
-pub fn tideman_example_5_ballots() -> Vec<[u16; 5]> {
+#[test]
+fn tideman_example_4_locked_graphs() {
+    let graphs = TabulatedData::from_ballots(&tideman_example_4_ballots(), 4)
+        .unwrap()
+        .locked_graphs();
+    assert_eq!(graphs.len(), 1);
+
+    let graph = graphs.into_iter().next().unwrap();
+    assert_eq!(graph.nodes(), 4);
+    assert_eq!(
+        graph.edges().collect::<Vec<_>>(),
+        // (ignored: 2 beats 0)
+        &[(0, 1), (0, 3), (1, 2), (1, 3), (2, 3)]
+    );
+}
+
+pub fn tideman_example_5_ballots() -> Vec<[Candidate; 5]> {
     [
         std::iter::repeat_n([0, 1, 2, 3, 4], 7),
         std::iter::repeat_n([4, 3, 0, 1, 2], 3),
@@ -243,7 +848,7 @@ fn tideman_example_5() {
     );
 }
 
-pub fn tideman_example_6_ballots() -> Vec<[u16; 4]> {
+pub fn tideman_example_6_ballots() -> Vec<[Candidate; 4]> {
     vec![[0, 1, 2, 3], [1, 2, 3, 0], [3, 2, 0, 1]]
 }
 
@@ -253,6 +858,899 @@ fn tideman_example_6() {
         tally(&tideman_example_6_ballots(), 4).unwrap(),
         BTreeSet::from([0, 1, 2, 3]),
     );
+
+    // every pair has the same margin, so there are many possible graphs
+    let graphs = TabulatedData::from_ballots(&tideman_example_6_ballots(), 4)
+        .unwrap()
+        .locked_graphs();
+    assert!(graphs.len() > 1);
+    // each of them has to skip at least one pair to avoid a cycle
+    assert!(graphs.iter().all(|graph| graph.edge_count() < 6));
+}
+
+#[test]
+fn ambiguity() {
+    for (ballots, candidates) in tideman_examples() {
+        let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+        assert_eq!(data.ambiguity(), data.locked_graphs().len());
+    }
+
+    // a strict ranking leaves no choice
+    let data = TabulatedData::from_ballots(&tideman_example_3_ballots(), 3).unwrap();
+    assert_eq!(data.ambiguity(), 1);
+
+    // each of the three equal wins in a cycle can be the one skipped
+    let data = TabulatedData::from_ballots([[0, 1, 2], [1, 2, 0], [2, 0, 1]], 3).unwrap();
+    assert_eq!(data.ambiguity(), 3);
+}
+
+#[test]
+fn ambiguous_margins() {
+    for (ballots, candidates) in tideman_examples() {
+        let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+        assert_eq!(data.ambiguous_margins().is_empty(), data.ambiguity() == 1);
+    }
+
+    // the cycle of equal wins can be broken in any of three ways
+    let data = TabulatedData::from_ballots([[0, 1, 2], [1, 2, 0], [2, 0, 1]], 3).unwrap();
+    assert_eq!(data.ambiguous_margins(), [1]);
+
+    // the two cycles are separate and have different margins
+    let data = TabulatedData::from_weighted_ballots_with(
+        [
+            ([0, 1], 2),
+            ([1, 2], 2),
+            ([2, 0], 2),
+            ([3, 4], 1),
+            ([4, 5], 1),
+            ([5, 3], 1),
+        ],
+        6,
+        TruncationMode::Ignore,
+    )
+    .unwrap();
+    assert_eq!(data.ambiguous_margins(), [2, 1]);
+    assert_eq!(data.ambiguity(), 9);
+
+    // tied wins that don't interact don't add any graphs
+    let data = TabulatedData::from_ballots([[0, 1], [2, 3]], 4).unwrap();
+    assert!(data.ambiguous_margins().is_empty());
+}
+
+#[test]
+fn weighted() {
+    for (ballots, candidates) in tideman_examples() {
+        // group the identical ballots together
+        let weighted = ballots.iter().counts();
+
+        assert_eq!(
+            TabulatedData::from_weighted_ballots(
+                weighted.iter().map(|(b, w)| (b, *w as u64)),
+                candidates
+            )
+            .unwrap(),
+            TabulatedData::from_ballots(&ballots, candidates).unwrap(),
+        );
+    }
+
+    assert_eq!(
+        TabulatedData::from_weighted_ballots([([0, 1], 3), ([1, 0], 2)], 2)
+            .unwrap()
+            .tally(),
+        BTreeSet::from([0]),
+    );
+    assert_eq!(
+        TabulatedData::from_weighted_ballots([([0, 1], 3), ([1, 0], 4)], 2)
+            .unwrap()
+            .tally(),
+        BTreeSet::from([1]),
+    );
+    assert_eq!(
+        TabulatedData::from_weighted_ballots([([0, 1], 3), ([4, 0], 0)], 2),
+        Err(Error::InvalidCandidate {
+            ballot: 1,
+            candidate: 4
+        }),
+    );
+}
+
+#[test]
+fn weighted_f64() {
+    let data = TabulatedData::from_weighted_ballots_f64(
+        [
+            ([0, 1].as_slice(), 1.5),
+            ([1, 0].as_slice(), 0.25),
+            ([1].as_slice(), 0.0),
+        ],
+        2,
+    )
+    .unwrap();
+    assert_eq!(data.tally(), BTreeSet::from([0]));
+    assert_eq!(data.total_ballots(), 7 * WEIGHT_SCALE / 4);
+    assert_eq!(
+        data.pairwise_results_with_margin()
+            .map(|(margin, pairs)| (margin, pairs.clone()))
+            .collect::<Vec<_>>(),
+        [(5 * WEIGHT_SCALE / 4, BTreeSet::from([(0, 1)]))]
+    );
+
+    // the same as whole weights, in different units
+    for (ballots, candidates) in tideman_examples() {
+        let weighted = ballots.iter().counts();
+        let data = TabulatedData::from_weighted_ballots_f64(
+            weighted.iter().map(|(b, w)| (b, *w as f64 / 4.0)),
+            candidates,
+        )
+        .unwrap();
+        assert_eq!(data.tally(), tally(&ballots, candidates).unwrap());
+    }
+
+    for weight in [-1.0, f64::NAN, f64::INFINITY, 1e300] {
+        assert_eq!(
+            TabulatedData::from_weighted_ballots_f64(
+                [([0].as_slice(), 1.0), ([1].as_slice(), weight)],
+                2
+            ),
+            Err(Error::InvalidWeight { ballot: 1 })
+        );
+    }
+    // errors are reported in ballot order
+    assert_eq!(
+        TabulatedData::from_weighted_ballots_f64(
+            [([0, 0].as_slice(), -1.0), ([1].as_slice(), -1.0)],
+            2
+        ),
+        Err(Error::InvalidBallot { ballot: 0 })
+    );
+}
+
+#[test]
+fn pairwise_results_with_margin() {
+    let data = TabulatedData::from_ballots(&tideman_example_4_ballots(), 4).unwrap();
+    assert_eq!(
+        data.pairwise_results_with_margin()
+            .map(|(margin, pairings)| (margin, pairings.clone()))
+            .collect::<Vec<_>>(),
+        [
+            (13, BTreeSet::from([(1, 2)])),
+            (9, BTreeSet::from([(0, 1)])),
+            (5, BTreeSet::from([(2, 0)])),
+            (3, BTreeSet::from([(0, 3), (1, 3), (2, 3)])),
+        ]
+    );
+    assert!(
+        data.pairwise_results_with_margin()
+            .map(|(_, pairings)| pairings)
+            .eq(data.pairwise_results())
+    );
+
+    assert!(
+        data.pairwise_results_ascending().eq(data
+            .pairwise_results()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev())
+    );
+    assert!(
+        data.pairwise_results_with_margin_ascending().eq(data
+            .pairwise_results_with_margin()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev())
+    );
+    assert_eq!(
+        data.pairwise_results_with_margin_ascending()
+            .map(|(margin, _)| margin)
+            .collect::<Vec<_>>(),
+        [3, 5, 9, 13]
+    );
+
+    let margins: BTreeMap<_, _> = data
+        .pairwise_results_with_margin()
+        .map(|(margin, pairings)| (margin, pairings.clone()))
+        .collect();
+    assert_eq!(data.into_pairwise_results(), margins);
+}
+
+#[test]
+fn pairwise_results_with_ties() {
+    // 0 and 1 are tied, and so are 1 and 2
+    let data = TabulatedData::from_ballots([[0, 1, 2], [1, 2, 0]], 3).unwrap();
+    let (table, ties) = data.into_pairwise_results_with_ties();
+    assert_eq!(table, BTreeMap::from([(2, BTreeSet::from([(1, 2)]))]));
+    assert_eq!(ties, BTreeSet::from([(0, 1), (0, 2)]));
+
+    for (ballots, candidates) in tideman_examples() {
+        let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+        let margins = data.margin_matrix();
+        let (table, ties) = data.into_pairwise_results_with_ties();
+        let wins: usize = table.values().map(|pairings| pairings.len()).sum();
+        assert_eq!(
+            wins + ties.len(),
+            (candidates * (candidates - 1) / 2) as usize
+        );
+        assert!(
+            ties.iter()
+                .all(|(a, b)| a < b && margins[*a as usize][*b as usize] == 0)
+        );
+    }
+}
+
+#[test]
+fn from_table() {
+    for (ballots, candidates) in tideman_examples() {
+        let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+        let tally = data.tally();
+        let margins = data.margin_matrix();
+
+        let data = TabulatedData::from_table(data.into_pairwise_results(), candidates).unwrap();
+        assert_eq!(data.tally(), tally);
+        assert_eq!(data.margin_matrix(), margins);
+    }
+
+    let data = TabulatedData::from_table(
+        BTreeMap::from([
+            (4, BTreeSet::new()),
+            (3, BTreeSet::from([(0, 1)])),
+            (2, BTreeSet::from([(1, 2)])),
+            (1, BTreeSet::from([(2, 0)])),
+        ]),
+        3,
+    )
+    .unwrap();
+    assert_eq!(data.tally(), BTreeSet::from([0]));
+    assert_eq!(data.pairwise_results().count(), 3);
+    assert_eq!(
+        data.pairwise_counts(),
+        BTreeMap::from([((0, 1), (3, 0)), ((0, 2), (0, 1)), ((1, 2), (2, 0))])
+    );
+
+    let invalid = |margin, winner, loser| {
+        TabulatedData::from_table(
+            BTreeMap::from([
+                (2, BTreeSet::from([(0, 1)])),
+                (margin, BTreeSet::from([(winner, loser)])),
+            ]),
+            3,
+        )
+    };
+    let error = |winner, loser| Err(Error::InvalidTable { winner, loser });
+    assert!(invalid(1, 1, 2).is_ok());
+    assert_eq!(invalid(0, 1, 2), error(1, 2));
+    assert_eq!(invalid(1, 1, 3), error(1, 3));
+    assert_eq!(invalid(1, 2, 2), error(2, 2));
+
+    // the pair that is found second is given, in order of increasing margin
+    assert_eq!(invalid(1, 1, 0), error(0, 1));
+    assert_eq!(invalid(1, 0, 1), error(0, 1));
+    assert_eq!(invalid(3, 1, 0), error(1, 0));
+    assert_eq!(
+        TabulatedData::from_table(BTreeMap::from([(2, BTreeSet::from([(0, 1), (1, 0)]))]), 2),
+        error(1, 0)
+    );
+}
+
+#[test]
+fn majority_graph() {
+    assert_eq!(
+        TabulatedData::from_ballots(&tideman_example_4_ballots(), 4)
+            .unwrap()
+            .majority_graph(),
+        [
+            (1, 2, 13),
+            (0, 1, 9),
+            (2, 0, 5),
+            (0, 3, 3),
+            (1, 3, 3),
+            (2, 3, 3)
+        ]
+    );
+
+    // ties are not majorities
+    assert_eq!(
+        TabulatedData::from_ballots([[0, 1, 2], [1, 0, 2]], 3)
+            .unwrap()
+            .majority_graph(),
+        [(0, 2, 2), (1, 2, 2)]
+    );
+}
+
+#[test]
+fn pairwise() {
+    use std::cmp::Ordering;
+
+    let data = TabulatedData::from_ballots(&tideman_example_4_ballots(), 4).unwrap();
+    assert_eq!(data.pairwise(0, 1), Ordering::Greater);
+    assert_eq!(data.pairwise(1, 0), Ordering::Less);
+    assert_eq!(data.pairwise(2, 0), Ordering::Greater);
+    assert_eq!(data.pairwise(3, 2), Ordering::Less);
+    assert_eq!(data.pairwise(3, 3), Ordering::Equal);
+
+    let data = TabulatedData::from_ballots([[0, 1, 2], [1, 0, 2]], 3).unwrap();
+    assert_eq!(data.pairwise(0, 1), Ordering::Equal);
+    assert_eq!(data.pairwise(1, 0), Ordering::Equal);
+    assert_eq!(data.pairwise(1, 2), Ordering::Greater);
+}
+
+#[test]
+fn head_to_head() {
+    use std::cmp::Ordering;
+
+    let ballots = [[0, 1, 2].as_slice(), &[1, 0, 2], &[2, 0], &[2]];
+    let data = TabulatedData::from_ballots(ballots, 4).unwrap();
+    assert_eq!(
+        data.head_to_head(0),
+        BTreeMap::from([
+            (1, Ordering::Greater),
+            (2, Ordering::Equal),
+            (3, Ordering::Greater)
+        ])
+    );
+    assert_eq!(
+        data.head_to_head(3),
+        BTreeMap::from([
+            (0, Ordering::Less),
+            (1, Ordering::Less),
+            (2, Ordering::Less)
+        ])
+    );
+    assert_eq!(data.head_to_head(4), BTreeMap::new());
+}
+
+#[test]
+#[should_panic]
+fn pairwise_invalid() {
+    let data = TabulatedData::from_ballots([[0, 1, 2], [1, 0, 2]], 3).unwrap();
+    data.pairwise(0, 3);
+}
+
+#[test]
+fn pairwise_counts() {
+    assert_eq!(
+        TabulatedData::from_ballots([[0, 1, 2].as_slice(), [1, 0].as_slice(), [1].as_slice()], 3)
+            .unwrap()
+            .pairwise_counts(),
+        BTreeMap::from([((0, 1), (1, 2)), ((0, 2), (2, 0)), ((1, 2), (3, 0))])
+    );
+
+    // ties are included
+    assert_eq!(
+        TabulatedData::from_ballots([[0, 1], [1, 0]], 3)
+            .unwrap()
+            .pairwise_counts(),
+        BTreeMap::from([((0, 1), (1, 1)), ((0, 2), (2, 0)), ((1, 2), (2, 0))])
+    );
+
+    assert!(
+        TabulatedData::from_ballots([[0; 0]; 0], 1)
+            .unwrap()
+            .pairwise_counts()
+            .is_empty()
+    );
+}
+
+#[test]
+fn margin_matrix() {
+    assert_eq!(
+        TabulatedData::from_ballots(&tideman_example_4_ballots(), 4)
+            .unwrap()
+            .margin_matrix(),
+        [
+            [0, 9, -5, 3],
+            [-9, 0, 13, 3],
+            [5, -13, 0, 3],
+            [-3, -3, -3, 0],
+        ],
+    );
+
+    // ties are zero
+    assert_eq!(
+        TabulatedData::from_ballots([[0, 1, 2], [1, 0, 2]], 3)
+            .unwrap()
+            .margin_matrix(),
+        [[0, 0, 2], [0, 0, 2], [-2, -2, 0]],
+    );
+
+    assert!(
+        TabulatedData::from_ballots([[0; 0]; 0], 0)
+            .unwrap()
+            .margin_matrix()
+            .is_empty()
+    );
+}
+
+#[test]
+fn bounded() {
+    let data = TabulatedData::from_ballots(&tideman_example_6_ballots(), 4).unwrap();
+    let graphs = data.locked_graphs().len();
+    assert_eq!(data.tally_bounded(graphs), Ok(data.tally()));
+    assert_eq!(data.tally_bounded(graphs - 1), Err(Error::TooManyTiebreaks));
+    assert_eq!(data.tally_bounded(0), Err(Error::TooManyTiebreaks));
+
+    // there is only ever one graph when there are no ties
+    let data = TabulatedData::from_ballots(&tideman_example_4_ballots(), 4).unwrap();
+    assert_eq!(data.tally_bounded(1), Ok(BTreeSet::from([0])));
+}
+
+#[test]
+fn deterministic() {
+    // every `GraphSet` gets its own random hash keys, so each tally iterates over the graphs in a
+    // different order, but the results should never change
+    for (ballots, candidates) in tideman_examples() {
+        let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+        let tally = data.tally();
+        let ranking = data.tally_ranking();
+        let graphs = data.locked_graphs();
+        for _ in 0..16 {
+            let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+            assert_eq!(data.tally(), tally);
+            assert!(data.tally().iter().eq(tally.iter()));
+            assert_eq!(data.tally_ranking(), ranking);
+            assert_eq!(data.locked_graphs(), graphs);
+        }
+    }
+}
+
+#[test]
+fn tally_partial() {
+    for (ballots, candidates) in tideman_examples() {
+        let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+        assert_eq!(data.tally_partial(1), data.tally());
+        assert_eq!(data.tally_partial(0), data.tally());
+        assert_eq!(data.tally_partial(u64::MAX), (0..candidates).collect());
+    }
+
+    // 1 over 2 has a margin of 4, and 0 over both has a margin of 2
+    let data = TabulatedData::from_weighted_ballots([([0, 1, 2], 3), ([1, 2, 0], 1)], 3).unwrap();
+    assert_eq!(data.tally_partial(5), BTreeSet::from([0, 1, 2]));
+    assert_eq!(data.tally_partial(4), BTreeSet::from([0, 1]));
+    assert_eq!(data.tally_partial(3), BTreeSet::from([0, 1]));
+    assert_eq!(data.tally_partial(2), BTreeSet::from([0]));
+}
+
+#[test]
+fn try_tally() {
+    for (ballots, candidates) in tideman_examples() {
+        let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+        assert_eq!(data.try_tally(), Ok(data.tally()));
+    }
+
+    // each cycle of three has three ways to be broken, and has a different margin from the
+    // others, so there are `3^cycles` graphs
+    let cycles = (crate::DEFAULT_MAX_GRAPHS as f64).log(3.0).ceil() as Candidate;
+    let ballots: Vec<([Candidate; 2], u64)> = (0..cycles)
+        .flat_map(|i| {
+            let weight = u64::from(i) + 1;
+            let (a, b, c) = (3 * i, 3 * i + 1, 3 * i + 2);
+            [([a, b], weight), ([b, c], weight), ([c, a], weight)]
+        })
+        .collect();
+    let data =
+        TabulatedData::from_weighted_ballots_with(ballots, 3 * cycles, TruncationMode::Ignore)
+            .unwrap();
+    assert_eq!(data.try_tally(), Err(Error::TooManyTiebreaks));
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel() {
+    use crate::{AcyclicGraph, GraphSet, lock_group_parallel, lock_group_sequential};
+
+    for (ballots, candidates) in tideman_examples() {
+        let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+
+        let mut graphs = GraphSet::from([AcyclicGraph::new(candidates)]);
+        for pairings in data.pairwise_results() {
+            let sequential = lock_group_sequential(graphs.clone(), pairings, usize::MAX).unwrap();
+            graphs = lock_group_parallel(graphs, pairings, usize::MAX).unwrap();
+            assert_eq!(sequential, graphs);
+        }
+
+        assert_eq!(
+            graphs
+                .iter()
+                .flat_map(|graph| graph.roots())
+                .collect::<BTreeSet<_>>(),
+            data.tally(),
+        );
+    }
+}
+
+#[test]
+fn streaming() {
+    for (ballots, candidates) in tideman_examples() {
+        // tabulate straight from an iterator that creates each ballot
+        let iter = (0..ballots.len()).map(|i| ballots[i].clone());
+        assert_eq!(
+            TabulatedData::from_ballots_iter(iter, candidates).unwrap(),
+            TabulatedData::from_ballots(&ballots, candidates).unwrap(),
+        );
+    }
+
+    assert_eq!(
+        TabulatedData::from_ballots_iter([vec![0, 1, 2], vec![0, 1, 0]], 3),
+        Err(Error::InvalidBallot { ballot: 1 })
+    );
+}
+
+#[test]
+fn total_ballots() {
+    for (ballots, candidates) in tideman_examples() {
+        let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+        assert_eq!(data.total_ballots(), ballots.len() as u64);
+    }
+
+    // empty ballots are still counted
+    let data = TabulatedData::from_weighted_ballots([(vec![0], 3), (vec![], 2)], 2).unwrap();
+    assert_eq!(data.total_ballots(), 5);
+    let data = TabulatedData::from_ballots_iter([vec![0], vec![]], 2).unwrap();
+    assert_eq!(data.total_ballots(), 2);
+
+    let data = TabulatedData::from_table(data.into_pairwise_results(), 2).unwrap();
+    assert_eq!(data.total_ballots(), 0);
+}
+
+#[test]
+fn all_pairwise() {
+    use crate::Comparison::{Tie, Win};
+
+    let data = TabulatedData::from_ballots([[2, 1], [1, 2]], 3).unwrap();
+    assert_eq!(
+        data.all_pairwise(),
+        [(1, 0, Win(2)), (2, 0, Win(2)), (1, 2, Tie)]
+    );
+
+    for (ballots, candidates) in tideman_examples() {
+        let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+        let wins: Vec<_> = data
+            .all_pairwise()
+            .into_iter()
+            .filter_map(|(winner, loser, comparison)| match comparison {
+                Win(margin) => Some((winner, loser, margin)),
+                Tie => None,
+            })
+            .sorted_by_key(|(winner, loser, margin)| (std::cmp::Reverse(*margin), *winner, *loser))
+            .collect();
+        assert_eq!(wins, data.majority_graph());
+    }
+}
+
+#[test]
+fn progress() {
+    for (ballots, candidates) in tideman_examples() {
+        let mut reports = Vec::new();
+        assert_eq!(
+            TabulatedData::from_ballots_with_progress(&ballots, candidates, |n| reports.push(n))
+                .unwrap(),
+            TabulatedData::from_ballots(&ballots, candidates).unwrap(),
+        );
+        assert_eq!(reports, [ballots.len()]);
+    }
+
+    // reported at every interval, and not again at the end
+    let ballots = vec![[0, 1]; 2 * crate::PROGRESS_INTERVAL];
+    let mut reports = Vec::new();
+    TabulatedData::from_ballots_with_progress(&ballots, 2, |n| reports.push(n)).unwrap();
+    assert_eq!(
+        reports,
+        [crate::PROGRESS_INTERVAL, 2 * crate::PROGRESS_INTERVAL]
+    );
+
+    // nothing is reported for an invalid ballot
+    let mut reports = Vec::new();
+    assert_eq!(
+        TabulatedData::from_ballots_with_progress([[0, 2]], 2, |n| reports.push(n)),
+        Err(Error::InvalidCandidate {
+            ballot: 0,
+            candidate: 2
+        })
+    );
+    assert!(reports.is_empty());
+}
+
+#[test]
+fn score_ballots() {
+    // strict scores are the same as the equivalent ranking
+    for (ballots, candidates) in tideman_examples() {
+        let scores = ballots.iter().map(|ballot| {
+            ballot
+                .iter()
+                .enumerate()
+                .map(|(i, c)| (*c, -(i as i32)))
+                .collect()
+        });
+        assert_eq!(
+            TabulatedData::from_score_ballots(scores, candidates).unwrap(),
+            TabulatedData::from_ballots(&ballots, candidates).unwrap(),
+        );
+    }
+
+    let data = TabulatedData::from_score_ballots(
+        [
+            BTreeMap::from([(0, 5), (1, 5), (2, 1)]),
+            BTreeMap::from([(0, 3), (1, 4)]),
+            BTreeMap::from([(2, 0)]),
+        ],
+        4,
+    )
+    .unwrap();
+    assert_eq!(
+        data.pairwise_counts(),
+        BTreeMap::from([
+            ((0, 1), (0, 1)),
+            ((0, 2), (2, 1)),
+            ((0, 3), (2, 0)),
+            ((1, 2), (2, 1)),
+            ((1, 3), (2, 0)),
+            ((2, 3), (2, 0)),
+        ])
+    );
+    assert_eq!(data.tally(), BTreeSet::from([1]));
+
+    assert_eq!(
+        TabulatedData::from_score_ballots([BTreeMap::from([(0, 1)]), BTreeMap::from([(4, 1)])], 4),
+        Err(Error::InvalidCandidate {
+            ballot: 1,
+            candidate: 4
+        })
+    );
+}
+
+#[test]
+fn named_ballots() {
+    let (data, names) = TabulatedData::from_named_ballots(
+        [vec!["b", "a"], vec!["b", "c", "a"], vec!["a"]],
+        &["a", "b", "c"],
+    )
+    .unwrap();
+    assert_eq!(
+        data,
+        TabulatedData::from_ballots(&[vec![1, 0], vec![1, 2, 0], vec![0]], 3).unwrap()
+    );
+    assert_eq!(names, ["a", "b", "c"]);
+    assert_eq!(data.tally(), BTreeSet::from([1]));
+    assert_eq!(decode_winners(&data.tally(), &names), ["b"]);
+
+    assert_eq!(
+        TabulatedData::from_named_ballots([vec!["a"], vec!["d"]], &["a", "b", "c"]),
+        Err(Error::InvalidCandidate {
+            ballot: 1,
+            candidate: 3
+        })
+    );
+    assert_eq!(
+        TabulatedData::from_named_ballots([vec!["a", "a"]], &["a"]),
+        Err(Error::InvalidBallot { ballot: 0 })
+    );
+}
+
+#[test]
+fn decode() {
+    let labels = ["a".to_string(), "b".to_string(), "c".to_string()];
+    assert_eq!(decode_winners(&BTreeSet::from([0, 2]), &labels), ["a", "c"]);
+    assert_eq!(decode_winners(&BTreeSet::from([1, 3, 7]), &labels), ["b"]);
+    assert!(decode_winners(&BTreeSet::new(), &labels).is_empty());
+}
+
+#[test]
+fn approval_ballots() {
+    let data = TabulatedData::from_approval_ballots(
+        [
+            BTreeSet::from([0, 1]),
+            BTreeSet::from([1]),
+            BTreeSet::from([2]),
+        ],
+        3,
+    )
+    .unwrap();
+    assert_eq!(
+        data.pairwise_counts(),
+        BTreeMap::from([((0, 1), (0, 1)), ((0, 2), (1, 1)), ((1, 2), (2, 1))])
+    );
+    assert_eq!(data.tally(), BTreeSet::from([1]));
+
+    // approving everyone or no one says nothing
+    let data =
+        TabulatedData::from_approval_ballots([BTreeSet::new(), BTreeSet::from([0, 1])], 2).unwrap();
+    assert_eq!(data.tally(), BTreeSet::from([0, 1]));
+    assert_eq!(data.total_ballots(), 2);
+
+    assert_eq!(
+        TabulatedData::from_approval_ballots([BTreeSet::from([2])], 2),
+        Err(Error::InvalidCandidate {
+            ballot: 0,
+            candidate: 2
+        })
+    );
+}
+
+#[test]
+fn comparator_ballots() {
+    // the same as ranked ballots, where earlier is better
+    for (ballots, candidates) in tideman_examples() {
+        let comparators = ballots.iter().map(|ballot| {
+            move |a, b| {
+                let position = |c| ballot.iter().position(|x| *x == c).unwrap_or(usize::MAX);
+                position(b).cmp(&position(a))
+            }
+        });
+        assert_eq!(
+            TabulatedData::from_comparator_ballots(comparators, candidates),
+            TabulatedData::from_ballots(&ballots, candidates).unwrap(),
+        );
+    }
+
+    // prefer the candidate with the value closest to each voter's ideal
+    let values = [1, 5, 9];
+    let data = TabulatedData::from_comparator_ballots(
+        [2, 4, 7, 9, 3].map(|ideal: i32| {
+            move |a: Candidate, b: Candidate| {
+                let distance = |c: Candidate| (values[c as usize] - ideal).abs();
+                distance(b).cmp(&distance(a))
+            }
+        }),
+        3,
+    );
+    assert_eq!(
+        data.pairwise_counts(),
+        // voters with ideals of 3 and 7 have no preference between two candidates
+        BTreeMap::from([((0, 1), (1, 3)), ((0, 2), (3, 2)), ((1, 2), (3, 1))])
+    );
+    assert_eq!(data.tally(), BTreeSet::from([1]));
+}
+
+#[test]
+fn dedup() {
+    for (ballots, candidates) in tideman_examples() {
+        assert_eq!(
+            TabulatedData::from_ballots_dedup(&ballots, candidates).unwrap(),
+            TabulatedData::from_ballots(&ballots, candidates).unwrap(),
+        );
+    }
+
+    assert_eq!(
+        TabulatedData::from_ballots_dedup([[0, 1, 2], [0, 1, 0]], 3),
+        Err(Error::InvalidBallot { ballot: 1 })
+    );
+
+    // the index is of the first invalid ballot, not the first distinct one
+    assert_eq!(
+        TabulatedData::from_ballots_dedup([[0, 1, 2], [0, 1, 2], [0, 1, 3], [0, 1, 3]], 3),
+        Err(Error::InvalidCandidate {
+            ballot: 2,
+            candidate: 3
+        })
+    );
+}
+
+/// Every Tideman example, along with the number of candidates
+pub fn tideman_examples() -> Vec<(Vec<Vec<Candidate>>, Candidate)> {
+    vec![
+        (filter_ballots(tideman_example_2_ballots(), |_| true), 5),
+        (filter_ballots(tideman_example_3_ballots(), |_| true), 3),
+        (filter_ballots(tideman_example_4_ballots(), |_| true), 4),
+        (filter_ballots(tideman_example_5_ballots(), |_| true), 5),
+        (filter_ballots(tideman_example_6_ballots(), |_| true), 4),
+    ]
+}
+
+#[test]
+fn ranking() {
+    // the winners from the ranking should always match the tally
+    for (ballots, candidates) in tideman_examples() {
+        let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+        let ranking = data.tally_ranking();
+        assert_eq!(ranking[0], data.tally());
+
+        // every candidate is in exactly one tier
+        assert_eq!(
+            ranking.iter().map(|tier| tier.len()).sum::<usize>(),
+            candidates as usize
+        );
+    }
+
+    assert_eq!(
+        TabulatedData::from_ballots(&tideman_example_4_ballots(), 4)
+            .unwrap()
+            .tally_ranking(),
+        [
+            BTreeSet::from([0]),
+            BTreeSet::from([1]),
+            BTreeSet::from([2]),
+            BTreeSet::from([3]),
+        ],
+    );
+
+    assert_eq!(
+        TabulatedData::from_ballots(&tideman_example_6_ballots(), 4)
+            .unwrap()
+            .tally_ranking(),
+        [BTreeSet::from([0, 1, 2, 3])],
+    );
+
+    assert!(
+        TabulatedData::from_ballots([[0; 0]; 0], 0)
+            .unwrap()
+            .tally_ranking()
+            .is_empty()
+    );
+}
+
+#[test]
+fn runner_up() {
+    for (ballots, candidates) in tideman_examples() {
+        let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+        assert_eq!(
+            data.runner_up(),
+            data.tally_ranking().get(1).cloned().unwrap_or_default()
+        );
+    }
+
+    let data = TabulatedData::from_ballots(&tideman_example_4_ballots(), 4).unwrap();
+    assert_eq!(data.runner_up(), BTreeSet::from([1]));
+
+    // 1 and 2 are tied for second
+    let data = TabulatedData::from_ballots([[0, 1, 2], [0, 2, 1]], 3).unwrap();
+    assert_eq!(data.runner_up(), BTreeSet::from([1, 2]));
+
+    let data = TabulatedData::from_ballots(&tideman_example_6_ballots(), 4).unwrap();
+    assert_eq!(data.runner_up(), BTreeSet::new());
+}
+
+#[test]
+fn consensus_ranking() {
+    for (ballots, candidates) in tideman_examples() {
+        let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+        let ranking = data.consensus_ranking();
+        assert!(ranking[0].is_superset(&data.tally()));
+        assert_eq!(
+            ranking.iter().map(|tier| tier.len()).sum::<usize>(),
+            candidates as usize
+        );
+    }
+
+    assert_eq!(
+        TabulatedData::from_ballots(&tideman_example_4_ballots(), 4)
+            .unwrap()
+            .consensus_ranking(),
+        [
+            BTreeSet::from([0]),
+            BTreeSet::from([1]),
+            BTreeSet::from([2]),
+            BTreeSet::from([3]),
+        ],
+    );
+
+    // 0 and 1 are tied
+    assert_eq!(
+        TabulatedData::from_ballots([[0, 1, 2], [1, 0, 2]], 3)
+            .unwrap()
+            .consensus_ranking(),
+        [BTreeSet::from([0, 1]), BTreeSet::from([2])],
+    );
+
+    // 0 beats everyone, and the rest form a cycle with equal margins
+    assert_eq!(
+        TabulatedData::from_ballots([[0, 1, 2, 3], [0, 2, 3, 1], [0, 3, 1, 2]], 4)
+            .unwrap()
+            .consensus_ranking(),
+        [BTreeSet::from([0]), BTreeSet::from([1, 2, 3])],
+    );
+
+    // 1 is tied with both 0 and 2, so it can't be placed above or below them
+    let data = TabulatedData::from_ballots(&[vec![0, 2], vec![1]], 3).unwrap();
+    assert_eq!(
+        data.tally_ranking(),
+        [BTreeSet::from([0, 1]), BTreeSet::from([2])]
+    );
+    assert_eq!(data.consensus_ranking(), [BTreeSet::from([0, 1, 2])]);
+
+    assert!(
+        TabulatedData::from_ballots([[0; 0]; 0], 0)
+            .unwrap()
+            .consensus_ranking()
+            .is_empty()
+    );
 }
 
 // Munger examples from: Munger, C.T. The best Condorcet-compatible election method: Ranked Pairs.
@@ -282,3 +1780,139 @@ fn munger_example_1() {
         BTreeSet::from([3]),
     );
 }
+
+/// Generate a number of candidates (at most `max`), and valid ballots for that many candidates
+///
+/// Small elections have many ties, which take time factorial in the number of pairs of candidates
+/// to tally, so `max` should be kept small.
+fn election(
+    max: Candidate,
+) -> impl proptest::strategy::Strategy<Value = (Candidate, Vec<Vec<Candidate>>)> {
+    use proptest::prelude::*;
+
+    (1..=max).prop_flat_map(|candidates| {
+        let ballot = Just((0..candidates).collect::<Vec<_>>())
+            .prop_shuffle()
+            .prop_flat_map(move |ballot| (Just(ballot), 0..=candidates as usize))
+            .prop_map(|(mut ballot, len)| {
+                ballot.truncate(len);
+                ballot
+            });
+        (Just(candidates), proptest::collection::vec(ballot, 0..16))
+    })
+}
+
+/// Generate an election, a candidate to clone, and whether the clone goes after the cloned
+/// candidate on each ballot
+#[allow(clippy::type_complexity)]
+fn cloned_election()
+-> impl proptest::strategy::Strategy<Value = (Candidate, Vec<Vec<Candidate>>, Candidate, Vec<bool>)>
+{
+    use proptest::prelude::*;
+
+    election(3).prop_flat_map(|(candidates, ballots)| {
+        let len = ballots.len();
+        (
+            Just(candidates),
+            Just(ballots),
+            0..candidates,
+            proptest::collection::vec(any::<bool>(), len),
+        )
+    })
+}
+
+/// Find the Condorcet winner directly from the ballots
+fn condorcet_winner_oracle(ballots: &[Vec<Candidate>], candidates: Candidate) -> Option<Candidate> {
+    // a ranked candidate beats any candidate ranked after it, as well as every unranked one
+    let prefers = |ballot: &Vec<Candidate>, a: Candidate, b: Candidate| match (
+        ballot.iter().position(|c| *c == a),
+        ballot.iter().position(|c| *c == b),
+    ) {
+        (Some(a), Some(b)) => a < b,
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+
+    (0..candidates).find(|a| {
+        (0..candidates).filter(|b| b != a).all(|b| {
+            let wins = ballots
+                .iter()
+                .filter(|ballot| prefers(ballot, *a, b))
+                .count();
+            let losses = ballots
+                .iter()
+                .filter(|ballot| prefers(ballot, b, *a))
+                .count();
+            wins > losses
+        })
+    })
+}
+
+proptest::proptest! {
+    // the same as the `tabulate` fuzz target
+    #[test]
+    fn arbitrary_ballots(bytes in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..64)) {
+        if let Some((candidates, ballots)) = bytes.split_first() {
+            let candidates = Candidate::from(*candidates % 5);
+            let ballots: Vec<Vec<Candidate>> = ballots
+                .split(|byte| *byte == u8::MAX)
+                .map(|ballot| ballot.iter().map(|c| Candidate::from(*c % 8)).collect())
+                .collect();
+
+            if let Ok(data) = TabulatedData::from_ballots(&ballots, candidates) {
+                let winners = data.tally();
+                proptest::prop_assert_eq!(winners.is_empty(), candidates == 0);
+                proptest::prop_assert!(winners.iter().all(|winner| *winner < candidates));
+            }
+        }
+    }
+
+    #[test]
+    fn always_a_winner((candidates, ballots) in election(4)) {
+        let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+        proptest::prop_assert!(!data.tally().is_empty());
+    }
+
+    #[test]
+    fn condorcet_winner_wins((candidates, ballots) in election(4)) {
+        let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+        let winner = condorcet_winner_oracle(&ballots, candidates);
+        proptest::prop_assert_eq!(data.condorcet_winner(), winner);
+        if let Some(winner) = winner {
+            proptest::prop_assert_eq!(data.tally(), BTreeSet::from([winner]));
+        }
+    }
+
+    #[test]
+    fn independence_of_clones((candidates, ballots, clone, after) in cloned_election()) {
+        // add a new candidate, next to `clone` on every ballot
+        let cloned: Vec<Vec<Candidate>> = ballots
+            .iter()
+            .zip(&after)
+            .map(|(ballot, after)| {
+                let mut ballot = ballot.clone();
+                if let Some(position) = ballot.iter().position(|c| *c == clone) {
+                    ballot.insert(position + usize::from(*after), candidates);
+                }
+                ballot
+            })
+            .collect();
+
+        let winners = tally(&ballots, candidates).unwrap();
+        let cloned_winners = tally(&cloned, candidates + 1).unwrap();
+
+        // the clones win exactly when the original candidate won
+        proptest::prop_assert_eq!(
+            winners.contains(&clone),
+            cloned_winners.contains(&clone) || cloned_winners.contains(&candidates)
+        );
+        // and every other candidate is unaffected
+        proptest::prop_assert_eq!(
+            winners.iter().filter(|c| **c != clone).collect::<Vec<_>>(),
+            cloned_winners
+                .iter()
+                .filter(|c| **c != clone && **c != candidates)
+                .collect::<Vec<_>>()
+        );
+    }
+}
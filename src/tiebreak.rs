@@ -0,0 +1,391 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec,
+    vec::Vec,
+};
+use core::cmp::Ordering;
+
+use crate::{AcyclicGraph, Candidate, Error, TabulatedData};
+
+impl TabulatedData {
+    /// Tally election results, breaking ties using a fixed priority order of candidates
+    ///
+    /// `priority` must contain every candidate exactly once, from highest to lowest priority.
+    /// When pairs with the same margin have to be ordered, the pair whose winner has the highest
+    /// priority is locked in first. If the winners are the same, the pair whose loser has the
+    /// lowest priority goes first. If there are still multiple winners at the end (because of
+    /// pairwise ties), the one with the highest priority is chosen.
+    ///
+    /// This only locks in a single graph, so it avoids the combinatorial explosion of
+    /// [TabulatedData::tally]. The winner is always one of the winners from
+    /// [TabulatedData::tally]. `None` is returned only if there are no candidates.
+    ///
+    /// # Errors
+    /// An error will be returned if `priority` is not an ordering of all of the candidates.
+    pub fn tally_with_tiebreak(&self, priority: &[Candidate]) -> Result<Option<Candidate>, Error> {
+        // the position of each candidate in the priority order
+        let mut rank = vec![None; self.candidates as usize];
+        for (i, c) in priority.iter().enumerate() {
+            match rank.get_mut(*c as usize) {
+                Some(r @ None) => *r = Some(i),
+                _ => return Err(Error::InvalidTiebreak),
+            }
+        }
+        let rank: Vec<usize> = rank
+            .into_iter()
+            .collect::<Option<_>>()
+            .ok_or(Error::InvalidTiebreak)?;
+        let rank = |c: Candidate| rank[c as usize];
+
+        let graph = lock_sorted(self.candidates, self.pairwise_results(), |a, b| {
+            rank(a.0)
+                .cmp(&rank(b.0))
+                .then_with(|| rank(b.1).cmp(&rank(a.1)))
+        });
+
+        Ok(graph.roots().min_by_key(|c| rank(*c)))
+    }
+
+    /// Tally election results, breaking ties in a fixed canonical order
+    ///
+    /// Pairs with the same margin are locked in ordered by `(winner, loser)`, and if there are
+    /// still multiple winners at the end (because of pairwise ties) the lowest-numbered one is
+    /// chosen. `None` is returned only if there are no candidates.
+    ///
+    /// This only locks in a single graph, so it takes polynomial time, while
+    /// [TabulatedData::tally] considers every order of each group of equal-margin pairs and so can
+    /// take factorial time in the size of the largest group. The winner is always one of the
+    /// winners from [TabulatedData::tally].
+    pub fn tally_deterministic(&self) -> Option<Candidate> {
+        lock_sorted(self.candidates, self.pairwise_results(), Ord::cmp)
+            .roots()
+            .next()
+    }
+
+    /// Tally election results, locking in pairs with the same margin in the order given by `cmp`
+    ///
+    /// `cmp` orders pairs, as `(winner, loser)`, and each group of pairs with the same margin is
+    /// locked in from the smallest pair to the largest. This allows any deterministic tie-break
+    /// policy, and only locks in a single graph, like [TabulatedData::tally_deterministic]. Every
+    /// root of that graph is returned, so there can still be multiple winners because of pairwise
+    /// ties. The winners are always a subset of the winners from [TabulatedData::tally].
+    pub fn tally_with_pair_order(
+        &self,
+        cmp: impl Fn(&(Candidate, Candidate), &(Candidate, Candidate)) -> Ordering,
+    ) -> BTreeSet<Candidate> {
+        lock_sorted(self.candidates, self.pairwise_results(), cmp)
+            .roots()
+            .collect()
+    }
+
+    /// Tally election results, breaking ties randomly
+    ///
+    /// Each group of pairs with the same margin is locked in a random order, and if there are
+    /// still multiple winners at the end (because of pairwise ties) one is chosen at random. The
+    /// same random number generator state always gives the same winner. `None` is returned only if
+    /// there are no candidates.
+    ///
+    /// This only locks in a single graph, like [TabulatedData::tally_deterministic]. The winner is
+    /// always one of the winners from [TabulatedData::tally].
+    #[cfg(feature = "rand")]
+    pub fn tally_random(&self, rng: &mut impl rand::Rng) -> Option<Candidate> {
+        use rand::seq::{IndexedRandom as _, SliceRandom as _};
+
+        let mut graph = AcyclicGraph::new(self.candidates);
+        for pairings in self.pairwise_results() {
+            let mut pairings: Vec<(Candidate, Candidate)> = pairings.iter().copied().collect();
+            pairings.shuffle(rng);
+
+            for (winner, loser) in pairings {
+                // just skip adding if it would add a cycle
+                graph.try_add_edge(winner, loser);
+            }
+        }
+
+        let roots: Vec<Candidate> = graph.roots().collect();
+        roots.choose(rng).copied()
+    }
+
+    /// Tally election results, breaking ties using the pairwise results themselves
+    ///
+    /// Ties are broken as in [TabulatedData::tally_with_tiebreak], except that the priority order
+    /// comes from the election: to order a group of pairs with the same margin, the candidates in
+    /// those pairs are ranked by applying this method recursively to only those candidates. The
+    /// same is done to choose between multiple roots of the final graph. Only when a tie involves
+    /// every candidate being ranked (so recursing would not make progress) are candidate numbers
+    /// used instead, as in [TabulatedData::tally_deterministic].
+    ///
+    /// The winner is always one of the winners from [TabulatedData::tally], and `None` is
+    /// returned only if there are no candidates. For each of the examples from Tideman's paper,
+    /// this gives the same winner as [TabulatedData::tally_deterministic], including example 6
+    /// where every candidate can win.
+    pub fn tally_deterministic_rp(&self) -> Option<Candidate> {
+        let candidates = (0..self.candidates).collect();
+        self.recursive_order(&candidates, &mut BTreeMap::new())
+            .first()
+            .copied()
+    }
+
+    /// Rank the given candidates, for [TabulatedData::tally_deterministic_rp]
+    fn recursive_order(
+        &self,
+        candidates: &BTreeSet<Candidate>,
+        memo: &mut BTreeMap<BTreeSet<Candidate>, Vec<Candidate>>,
+    ) -> Vec<Candidate> {
+        if candidates.len() <= 1 {
+            return candidates.iter().copied().collect();
+        } else if let Some(order) = memo.get(candidates) {
+            return order.clone();
+        }
+
+        // rank a subset of the candidates, using numbers if it is every candidate being ranked
+        let mut rank_subset = |subset: BTreeSet<Candidate>| -> BTreeMap<Candidate, usize> {
+            let order = if subset.len() < candidates.len() {
+                self.recursive_order(&subset, memo)
+            } else {
+                subset.into_iter().collect()
+            };
+            order.into_iter().enumerate().map(|(i, c)| (c, i)).collect()
+        };
+
+        let mut graph = AcyclicGraph::new(self.candidates);
+        for pairings in self.pairwise_results() {
+            let mut pairings: Vec<(Candidate, Candidate)> = pairings
+                .iter()
+                .copied()
+                .filter(|(w, l)| candidates.contains(w) && candidates.contains(l))
+                .collect();
+
+            let rank = rank_subset(pairings.iter().flat_map(|(w, l)| [*w, *l]).collect());
+            pairings.sort_by(|a, b| {
+                rank[&a.0]
+                    .cmp(&rank[&b.0])
+                    .then_with(|| rank[&b.1].cmp(&rank[&a.1]))
+            });
+
+            for (winner, loser) in pairings {
+                // just skip adding if it would add a cycle
+                graph.try_add_edge(winner, loser);
+            }
+        }
+
+        // take the highest ranked root each time
+        let mut remaining = candidates.clone();
+        let mut order = Vec::new();
+        while !remaining.is_empty() {
+            let roots: BTreeSet<Candidate> = graph.subgraph_roots(&remaining).collect();
+            let rank = rank_subset(roots);
+            let next = *rank.iter().min_by_key(|(_, i)| **i).unwrap().0;
+            remaining.remove(&next);
+            order.push(next);
+        }
+
+        memo.insert(candidates.clone(), order.clone());
+        order
+    }
+}
+
+/// Lock in each group of equal-strength pairings in a single order
+///
+/// The groups must be given from strongest to weakest. Within each group, the pairings are locked
+/// in the order given by `cmp`.
+pub(crate) fn lock_sorted<'a>(
+    candidates: Candidate,
+    groups: impl IntoIterator<Item = &'a BTreeSet<(Candidate, Candidate)>>,
+    cmp: impl Fn(&(Candidate, Candidate), &(Candidate, Candidate)) -> Ordering,
+) -> AcyclicGraph {
+    let mut graph = AcyclicGraph::new(candidates);
+
+    for pairings in groups {
+        let mut pairings: Vec<(Candidate, Candidate)> = pairings.iter().copied().collect();
+        pairings.sort_by(&cmp);
+
+        for (winner, loser) in pairings {
+            // just skip adding if it would add a cycle
+            graph.try_add_edge(winner, loser);
+        }
+    }
+
+    graph
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use itertools::Itertools as _;
+
+    use crate::{Candidate, Error, TabulatedData};
+
+    #[test]
+    fn priority() {
+        let data =
+            TabulatedData::from_ballots(&crate::test::tideman_example_6_ballots(), 4).unwrap();
+        assert_eq!(data.tally_with_tiebreak(&[0, 1, 2, 3]), Ok(Some(0)));
+        assert_eq!(data.tally_with_tiebreak(&[3, 2, 1, 0]), Ok(Some(1)));
+
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+            let winners = data.tally();
+
+            let forward = (0..candidates).collect::<Vec<_>>();
+            let winner = data.tally_with_tiebreak(&forward).unwrap().unwrap();
+            assert!(winners.contains(&winner));
+
+            let reverse = (0..candidates).rev().collect::<Vec<_>>();
+            let winner = data.tally_with_tiebreak(&reverse).unwrap().unwrap();
+            assert!(winners.contains(&winner));
+        }
+
+        // pairwise ties are broken by the priority too
+        let data = TabulatedData::from_ballots([[0, 1, 2], [1, 0, 2]], 3).unwrap();
+        assert_eq!(data.tally_with_tiebreak(&[1, 2, 0]), Ok(Some(1)));
+        assert_eq!(data.tally_with_tiebreak(&[2, 0, 1]), Ok(Some(0)));
+
+        let data = TabulatedData::from_ballots([[0; 0]; 0], 0).unwrap();
+        assert_eq!(data.tally_with_tiebreak(&[]), Ok(None));
+    }
+
+    #[test]
+    fn deterministic() {
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+            let winner = data.tally_deterministic().unwrap();
+            assert!(data.tally().contains(&winner));
+        }
+
+        let data =
+            TabulatedData::from_ballots(&crate::test::tideman_example_6_ballots(), 4).unwrap();
+        assert_eq!(data.tally_deterministic(), Some(0));
+
+        let data = TabulatedData::from_ballots([[0; 0]; 0], 0).unwrap();
+        assert_eq!(data.tally_deterministic(), None);
+    }
+
+    #[test]
+    fn pair_order() {
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+            let winners = data.tally_with_pair_order(Ord::cmp);
+            assert_eq!(winners.first().copied(), data.tally_deterministic());
+            assert!(winners.is_subset(&data.tally()));
+            assert!(
+                data.tally_with_pair_order(|a, b| b.cmp(a))
+                    .is_subset(&data.tally())
+            );
+        }
+
+        // the same order as a priority tie-break gives the same winner
+        let data =
+            TabulatedData::from_ballots(&crate::test::tideman_example_6_ballots(), 4).unwrap();
+        for priority in (0..4).permutations(4) {
+            let rank = |c: Candidate| priority.iter().position(|p| *p == c);
+            let winners = data.tally_with_pair_order(|a, b| {
+                rank(a.0)
+                    .cmp(&rank(b.0))
+                    .then_with(|| rank(b.1).cmp(&rank(a.1)))
+            });
+            assert_eq!(
+                winners.into_iter().min_by_key(|c| rank(*c)),
+                data.tally_with_tiebreak(&priority).unwrap()
+            );
+        }
+
+        // pairwise ties give multiple winners
+        let data = TabulatedData::from_ballots([[0; 0]; 0], 3).unwrap();
+        assert_eq!(
+            data.tally_with_pair_order(Ord::cmp),
+            BTreeSet::from([0, 1, 2])
+        );
+    }
+
+    #[test]
+    fn deterministic_rp() {
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+            let winner = data.tally_deterministic_rp().unwrap();
+            assert!(data.tally().contains(&winner));
+            assert_eq!(Some(winner), data.tally_deterministic());
+        }
+
+        // 2 over 0 and 0 over 1 have the same margin, and ranking 0, 1, and 2 by themselves puts 2
+        // first, so 2 over 0 is locked in first
+        let data = TabulatedData::from_ballots(
+            &[vec![3, 2], vec![1, 3], vec![2, 0, 1], vec![0, 1, 3, 2]],
+            4,
+        )
+        .unwrap();
+        assert_eq!(data.tally(), BTreeSet::from([0, 1]));
+        assert_eq!(data.tally_deterministic(), Some(0));
+        assert_eq!(data.tally_deterministic_rp(), Some(1));
+
+        let data = TabulatedData::from_ballots([[0; 0]; 0], 0).unwrap();
+        assert_eq!(data.tally_deterministic_rp(), None);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn random() {
+        use rand::SeedableRng as _;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        for (ballots, candidates) in crate::test::tideman_examples() {
+            let data = TabulatedData::from_ballots(&ballots, candidates).unwrap();
+            let winner = data.tally_random(&mut rng).unwrap();
+            assert!(data.tally().contains(&winner));
+        }
+
+        // every candidate can win, and with enough tries every one does
+        let data =
+            TabulatedData::from_ballots(&crate::test::tideman_example_6_ballots(), 4).unwrap();
+        let winners: BTreeSet<_> = (0..100)
+            .map(|_| data.tally_random(&mut rng).unwrap())
+            .collect();
+        assert_eq!(winners, data.tally());
+
+        // the same seed gives the same winners
+        let winners = |seed| {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+            (0..10)
+                .map(|_| data.tally_random(&mut rng).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(winners(1), winners(1));
+
+        // ties are broken too
+        let data = TabulatedData::from_ballots([[0; 0]; 0], 3).unwrap();
+        let winners: BTreeSet<_> = (0..100)
+            .map(|_| data.tally_random(&mut rng).unwrap())
+            .collect();
+        assert_eq!(winners, BTreeSet::from([0, 1, 2]));
+
+        let data = TabulatedData::from_ballots([[0; 0]; 0], 0).unwrap();
+        assert_eq!(data.tally_random(&mut rng), None);
+    }
+
+    #[test]
+    fn deterministic_large_tie() {
+        // every one of the 190 pairs has a margin of one, so an exhaustive tally would need to try
+        // 190! orders, but this only needs to try one
+        let data = TabulatedData::from_ballots(&[(0..20).collect::<Vec<_>>()], 20).unwrap();
+        assert_eq!(data.pairwise_results().count(), 1);
+        assert_eq!(data.tally_deterministic(), Some(0));
+    }
+
+    #[test]
+    fn invalid_priority() {
+        let data = TabulatedData::from_ballots([[0, 1, 2], [1, 0, 2]], 3).unwrap();
+        assert_eq!(
+            data.tally_with_tiebreak(&[0, 1]),
+            Err(Error::InvalidTiebreak)
+        );
+        assert_eq!(
+            data.tally_with_tiebreak(&[0, 1, 1]),
+            Err(Error::InvalidTiebreak)
+        );
+        assert_eq!(
+            data.tally_with_tiebreak(&[0, 1, 2, 3]),
+            Err(Error::InvalidTiebreak)
+        );
+    }
+}